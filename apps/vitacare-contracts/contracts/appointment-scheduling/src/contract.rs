@@ -1,11 +1,46 @@
 use soroban_sdk::{
     contract, contractimpl,
-    log, map, vec,
-    Address, Env, Map, Vec,
+    log, symbol_short, vec,
+    Address, BytesN, Env, Error as SorobanError, String, Vec,
 };
 
-use super::error::Error;
-use super::storage_types::{Appointment, AppointmentStatus, DataKey};
+use super::credentials;
+use super::delegation;
+use super::error::{Error, GroupError};
+use super::group;
+use super::payments::{self, BPS_DENOMINATOR, MAX_LATE_CANCEL_WINDOW};
+use super::records;
+use super::stats;
+use super::storage::{self, SECONDS_PER_DAY};
+use super::storage_types::{
+    Appointment, AppointmentPage, AppointmentRating, AppointmentSeries, AppointmentStatus,
+    Attachment, AttachmentKind, AvailabilityWindow, Cancellation, CancellationInitiator, CancellationReason,
+    CancellationRecord,
+    ConsultationFee, DataKey, DoctorReputation, DoctorStats, GroupSession, Handover, Modality, Payment, Priority, RescheduleProposal,
+    RescheduleRecord, SlotOffer, StatusChange, TelemedicineSession, VisitDetails, WaitlistEntry,
+};
+use super::waitlist;
+
+/// Upper bound on `limit` for paginated queries.
+const MAX_PAGE_SIZE: u32 = 50;
+/// Patients may check in from this long before the start time...
+const CHECK_IN_OPENS_BEFORE: u64 = 30 * 60;
+/// ...until this long after it.
+const CHECK_IN_CLOSES_AFTER: u64 = 15 * 60;
+/// Time after the start a doctor must wait before marking a no-show.
+const NO_SHOW_GRACE_PERIOD: u64 = 15 * 60;
+/// Time after the end of a still scheduled appointment before anyone may expire it.
+const EXPIRY_PERIOD: u64 = 24 * 60 * 60;
+/// Most consecutive slots of a window a single visit may take.
+const MAX_SLOTS_PER_VISIT: u64 = 4;
+/// Most occurrences a single recurring series may book.
+const MAX_SERIES_LENGTH: u32 = 52;
+/// Participants may join a telemedicine session from this long before the start
+/// time until the scheduled end.
+const SESSION_OPENS_BEFORE: u64 = 10 * 60;
+/// Bounds of a rating score.
+const MIN_RATING: u32 = 1;
+const MAX_RATING: u32 = 5;
 
 #[contract]
 pub struct AppointmentSchedulingContract;
@@ -13,139 +48,1721 @@ pub struct AppointmentSchedulingContract;
 #[contractimpl]
 impl AppointmentSchedulingContract {
 
-    pub fn initialize(env: Env) -> Result<(), Error> {
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        admin.require_auth();
+
         if env.storage().instance().has(&DataKey::NextAppointmentId) {
              return Err(Error::AlreadyInitialized);
         }
 
+        env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage().instance().set(&DataKey::NextAppointmentId, &1u64);
-        let appointments: Map<u64, Appointment> = map![&env];
-        env.storage().persistent().set(&DataKey::Appointments, &appointments);
         log!(&env, "Contract initialized. Appointment counter set to 1.");
 
         Ok(())
     }
 
+    /// Points booking at the deployed doctor-credentials contract. Once set,
+    /// only doctors registered and verified there can be booked.
+    pub fn set_doctor_credentials_contract(env: Env, credentials: Address) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        env.storage().instance().set(&DataKey::DoctorCredentialsContract, &credentials);
+
+        log!(&env, "Doctor credentials contract set: {}", credentials);
+        Ok(())
+    }
+
+    /// Caps how many appointments in `Scheduled` status a single patient may hold.
+    pub fn set_patient_appointment_limit(env: Env, limit: u32) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        if limit == 0 {
+            return Err(Error::InvalidLimit);
+        }
+        env.storage().instance().set(&DataKey::PatientAppointmentLimit, &limit);
+
+        log!(&env, "Patient appointment limit set: {}", limit);
+        Ok(())
+    }
+
+    pub fn get_patient_appointment_limit(env: Env) -> Option<u32> {
+        env.storage().instance().get(&DataKey::PatientAppointmentLimit)
+    }
+
+    /// Sets the medical records contract that `complete_appointment_with_record` files into.
+    pub fn set_medical_records_contract(env: Env, records: Address) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        env.storage().instance().set(&DataKey::MedicalRecordsContract, &records);
+
+        log!(&env, "Medical records contract set: {}", records);
+        Ok(())
+    }
+
+    /// Publishes a bookable window for `doctor`, divided into slots of
+    /// `slot_duration` seconds starting at `start`. Windows of the same doctor
+    /// may not overlap.
+    pub fn add_availability(
+        env: Env,
+        doctor: Address,
+        start: u64,
+        end: u64,
+        slot_duration: u64,
+    ) -> Result<u64, Error> {
+        doctor.require_auth();
+        Self::publish_window(&env, &doctor, start, end, slot_duration, false)
+    }
+
+    /// Same as `add_availability`, but the slots are held for urgent and
+    /// emergency visits booked by clinic staff.
+    pub fn add_urgent_availability(
+        env: Env,
+        doctor: Address,
+        start: u64,
+        end: u64,
+        slot_duration: u64,
+    ) -> Result<u64, Error> {
+        doctor.require_auth();
+        Self::publish_window(&env, &doctor, start, end, slot_duration, true)
+    }
+
+    /// Withdraws a published window. Appointments already booked in it are kept.
+    pub fn remove_availability(env: Env, doctor: Address, window_id: u64) -> Result<(), Error> {
+        doctor.require_auth();
+
+        let mut windows = Self::get_availability(env.clone(), doctor.clone());
+        let index = windows
+            .iter()
+            .position(|window| window.id == window_id)
+            .ok_or(Error::AvailabilityNotFound)?;
+        windows.remove(index as u32);
+        env.storage().persistent().set(&DataKey::Availability(doctor), &windows);
+
+        log!(&env, "Availability removed: ID={}", window_id);
+        Ok(())
+    }
+
+    pub fn get_availability(env: Env, doctor: Address) -> Vec<AvailabilityWindow> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Availability(doctor))
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    /// Lists the start times of the slots in `window_id` that are still free.
+    pub fn get_available_slots(env: Env, doctor: Address, window_id: u64) -> Result<Vec<u64>, Error> {
+        let window = Self::get_availability(env.clone(), doctor.clone())
+            .iter()
+            .find(|window| window.id == window_id)
+            .ok_or(Error::AvailabilityNotFound)?;
+
+        let busy = busy_intervals(&env, &doctor, window.start, window.end, None);
+        let now = env.ledger().timestamp();
+        let mut slots: Vec<u64> = vec![&env];
+        let mut slot = window.start;
+        while slot + window.slot_duration <= window.end {
+            if slot > now && !overlaps(&busy, slot, window.slot_duration) {
+                slots.push_back(slot);
+            }
+            slot += window.slot_duration;
+        }
+        Ok(slots)
+    }
+
+    /// Sets the fee `doctor` charges for new bookings. Appointments already
+    /// booked keep the terms they were paid under. `late_cancel_window` may be
+    /// at most `MAX_LATE_CANCEL_WINDOW`.
+    pub fn set_consultation_fee(
+        env: Env,
+        doctor: Address,
+        token: Address,
+        amount: i128,
+        late_cancel_window: u64,
+        late_cancel_penalty_bps: u32,
+    ) -> Result<(), Error> {
+        doctor.require_auth();
+
+        if amount <= 0
+            || late_cancel_penalty_bps as i128 > BPS_DENOMINATOR
+            || late_cancel_window > MAX_LATE_CANCEL_WINDOW
+        {
+            return Err(Error::InvalidFee);
+        }
+
+        let fee = ConsultationFee {
+            token,
+            amount,
+            late_cancel_window,
+            late_cancel_penalty_bps,
+        };
+        env.storage().persistent().set(&DataKey::ConsultationFee(doctor.clone()), &fee);
+
+        log!(&env, "Consultation fee set: Doctor={}, Amount={}", doctor, amount);
+        Ok(())
+    }
+
+    /// Makes new bookings with `doctor` free again.
+    pub fn remove_consultation_fee(env: Env, doctor: Address) {
+        doctor.require_auth();
+        env.storage().persistent().remove(&DataKey::ConsultationFee(doctor));
+    }
+
+    pub fn get_consultation_fee(env: Env, doctor: Address) -> Option<ConsultationFee> {
+        env.storage().persistent().get(&DataKey::ConsultationFee(doctor))
+    }
+
+    /// Caps how many appointments `doctor` takes per calendar day (UTC).
+    /// Canceled appointments do not count.
+    pub fn set_daily_capacity(env: Env, doctor: Address, capacity: u32) -> Result<(), Error> {
+        doctor.require_auth();
+        if capacity == 0 {
+            return Err(Error::InvalidLimit);
+        }
+        env.storage().persistent().set(&DataKey::DailyCapacity(doctor.clone()), &capacity);
+
+        log!(&env, "Daily capacity set: Doctor={}, Capacity={}", doctor, capacity);
+        Ok(())
+    }
+
+    pub fn remove_daily_capacity(env: Env, doctor: Address) {
+        doctor.require_auth();
+        env.storage().persistent().remove(&DataKey::DailyCapacity(doctor));
+    }
+
+    pub fn get_daily_capacity(env: Env, doctor: Address) -> Option<u32> {
+        env.storage().persistent().get(&DataKey::DailyCapacity(doctor))
+    }
+
+    /// Books `datetime` with `doctor`. The visit must start on a published slot
+    /// and, over its full `details.duration`, must not overlap another booking.
+    /// The doctor's daily capacity and the patient appointment limit apply.
+    /// If the doctor charges a fee, it is escrowed from the patient, or from
+    /// the delegate or staff member booking for them.
     pub fn create_appointment(
         env: Env,
         patient: Address,
         doctor: Address,
         datetime: u64,
+        details: VisitDetails,
     ) -> Result<u64, Error> {
         patient.require_auth();
+        Self::book(&env, &patient, &patient, &doctor, datetime, details, Priority::Routine, None)
+    }
+
+    /// Books for `patient` on the signature of one of their delegates.
+    pub fn create_appointment_on_behalf(
+        env: Env,
+        delegate: Address,
+        patient: Address,
+        doctor: Address,
+        datetime: u64,
+        details: VisitDetails,
+    ) -> Result<u64, Error> {
+        delegation::require_acts_for(&env, &patient, &delegate)?;
+        Self::book(&env, &patient, &delegate, &doctor, datetime, details, Priority::Routine, None)
+    }
+
+    /// Registers `staff` as a clinic staff member of `institution`, allowed to
+    /// book urgent visits with the doctors that institution verified.
+    pub fn add_clinic_staff(env: Env, staff: Address, institution: Address) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        env.storage().persistent().set(&DataKey::ClinicStaff(staff.clone()), &institution);
+
+        log!(&env, "Clinic staff added: {}, Institution={}", staff, institution);
+        Ok(())
+    }
+
+    pub fn remove_clinic_staff(env: Env, staff: Address) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        env.storage().persistent().remove(&DataKey::ClinicStaff(staff.clone()));
+
+        log!(&env, "Clinic staff removed: {}", staff);
+        Ok(())
+    }
+
+    pub fn is_clinic_staff(env: Env, staff: Address) -> bool {
+        env.storage().persistent().has(&DataKey::ClinicStaff(staff))
+    }
+
+    /// The institution `staff` was registered for, if any.
+    pub fn get_clinic_staff_institution(env: Env, staff: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::ClinicStaff(staff))
+    }
+
+    /// Books an urgent or emergency visit for `patient` on behalf of clinic
+    /// `staff`. `doctor` must be verified by the staff member's institution.
+    /// Urgent-only slots are open to it, and scheduled bookings of a lower
+    /// priority in the way are cancelled with a full refund and a `displaced`
+    /// event to their patient.
+    pub fn create_urgent_appointment(
+        env: Env,
+        staff: Address,
+        patient: Address,
+        doctor: Address,
+        datetime: u64,
+        details: VisitDetails,
+        priority: Priority,
+    ) -> Result<u64, Error> {
+        staff.require_auth();
+
+        let institution = Self::get_clinic_staff_institution(env.clone(), staff.clone())
+            .ok_or(Error::NotClinicStaff)?;
+        if credentials::get_institution(&env, &doctor)? != Some(institution) {
+            return Err(Error::Unauthorized);
+        }
+        if priority == Priority::Routine {
+            return Err(Error::InvalidPriority);
+        }
+
+        let end = datetime + details.duration;
+        for day in storage::days_spanned(datetime, end) {
+            for mut appointment in storage::get_doctor_day_appointments(&env, &doctor, day).iter() {
+                let is_displaceable = appointment.status == AppointmentStatus::Scheduled
+                    && appointment.priority < priority;
+                if is_displaceable && appointment.datetime < end && datetime < appointment.end_time() {
+                    Self::displace(&env, &mut appointment, &staff);
+                }
+            }
+        }
 
-        let current_time = env.ledger().timestamp();
-        if datetime <= current_time {
-             return Err(Error::PastTimestamp);
+        Self::book(&env, &patient, &staff, &doctor, datetime, details, priority, None)
+    }
+
+    /// Books `count` appointments `interval` seconds apart under one series ID.
+    /// Every occurrence is checked before any is booked, so a single
+    /// conflicting slot fails the whole series.
+    pub fn create_appointment_series(
+        env: Env,
+        patient: Address,
+        doctor: Address,
+        first_datetime: u64,
+        interval: u64,
+        count: u32,
+        details: VisitDetails,
+    ) -> Result<u64, Error> {
+        patient.require_auth();
+
+        if count == 0 || count > MAX_SERIES_LENGTH || interval < details.duration {
+            return Err(Error::InvalidSeries);
+        }
+        validate_visit_details(&details)?;
+        credentials::require_verified_doctor(&env, &doctor)?;
+        for i in 0..count as u64 {
+            let datetime = first_datetime + i * interval;
+            Self::check_slot_free(&env, &doctor, datetime, details.duration, Priority::Routine, None)?;
         }
 
-        let next_id: u64 = env
+        let series_id: u64 = env
             .storage()
             .instance()
-            .get(&DataKey::NextAppointmentId)
-            .ok_or(Error::NotInitialized)?;
+            .get(&DataKey::NextSeriesId)
+            .unwrap_or(1);
+        env.storage().instance().set(&DataKey::NextSeriesId, &(series_id + 1));
 
-        let mut appointments: Map<u64, Appointment> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Appointments)
-            .ok_or(Error::NotInitialized)?;
+        let mut appointment_ids: Vec<u64> = vec![&env];
+        for i in 0..count as u64 {
+            let datetime = first_datetime + i * interval;
+            let appointment_id = Self::book(
+                &env,
+                &patient,
+                &patient,
+                &doctor,
+                datetime,
+                details.clone(),
+                Priority::Routine,
+                Some(series_id),
+            )?;
+            appointment_ids.push_back(appointment_id);
+        }
 
-        let appointment_id = next_id;
-        let new_appointment = Appointment {
-            id: appointment_id,
-            patient: patient.clone(),
-            doctor: doctor.clone(),
-            datetime,
-            status: AppointmentStatus::Scheduled,
+        let series = AppointmentSeries {
+            id: series_id,
+            patient,
+            doctor,
+            interval,
+            appointment_ids,
+            created_at: env.ledger().timestamp(),
         };
+        env.storage().persistent().set(&DataKey::Series(series_id), &series);
 
-        appointments.set(appointment_id, new_appointment);
-    
-        env.storage().persistent().set(&DataKey::Appointments, &appointments);
+        log!(&env, "Appointment series created: ID={}, Count={}", series_id, count);
+        Ok(series_id)
+    }
 
-        env.storage().instance().set(&DataKey::NextAppointmentId, &(next_id + 1));
+    /// Cancels every occurrence of the series that is still scheduled and has
+    /// not started yet, as patient cancellations. Single occurrences are
+    /// cancelled with `cancel_appointment`. Returns how many were cancelled.
+    pub fn cancel_remaining_series(env: Env, series_id: u64) -> Result<u32, Error> {
+        let series = Self::get_series(env.clone(), series_id)?;
+        series.patient.require_auth();
 
-        log!(&env, "Appointment created: ID={}, Patient={}, Doctor={}, Time={}",
-            appointment_id, patient, doctor, datetime);
+        let now = env.ledger().timestamp();
+        let mut cancelled = 0;
+        for appointment_id in series.appointment_ids.iter() {
+            let mut appointment = storage::get_appointment(&env, appointment_id)?;
+            if appointment.status == AppointmentStatus::Scheduled && appointment.datetime > now {
+                Self::cancel_by_patient(&env, &mut appointment, series.patient.clone());
+                cancelled += 1;
+            }
+        }
 
-        Ok(appointment_id)
+        log!(&env, "Appointment series canceled: ID={}, Count={}", series_id, cancelled);
+        Ok(cancelled)
     }
 
-
-    pub fn cancel_appointment(env: Env, appointment_id: u64) -> Result<(), Error> {
-        let mut appointments: Map<u64, Appointment> = env
-            .storage()
+    pub fn get_series(env: Env, series_id: u64) -> Result<AppointmentSeries, Error> {
+        env.storage()
             .persistent()
-            .get(&DataKey::Appointments)
-            .ok_or(Error::NotInitialized)?;
+            .get(&DataKey::Series(series_id))
+            .ok_or(Error::SeriesNotFound)
+    }
 
-        let mut appointment = appointments
-            .get(appointment_id)
-            .ok_or(Error::AppointmentNotFound)?;
-        appointment.patient.require_auth();
+    /// Cancels on the patient's behalf and refunds any escrowed fee, minus the
+    /// late-cancellation penalty if it applies. The freed slot is offered to
+    /// the doctor's waitlist.
+    pub fn cancel_appointment(env: Env, appointment_id: u64) -> Result<(), Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+        let patient = appointment.patient.clone();
+        patient.require_auth();
 
         if appointment.status != AppointmentStatus::Scheduled {
             return Err(Error::InvalidStatus);
         }
 
-        appointment.status = AppointmentStatus::Canceled;
+        Self::cancel_by_patient(&env, &mut appointment, patient);
+        Ok(())
+    }
+
+    /// Same as `cancel_appointment`, signed by one of the patient's delegates.
+    pub fn cancel_appointment_on_behalf(
+        env: Env,
+        delegate: Address,
+        appointment_id: u64,
+    ) -> Result<(), Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+        delegation::require_acts_for(&env, &appointment.patient, &delegate)?;
 
-        appointments.set(appointment_id, appointment.clone());
-        env.storage().persistent().set(&DataKey::Appointments, &appointments);
+        if appointment.status != AppointmentStatus::Scheduled {
+            return Err(Error::InvalidStatus);
+        }
 
-        log!(&env, "Appointment canceled: ID={}", appointment_id);
+        Self::cancel_by_patient(&env, &mut appointment, delegate);
         Ok(())
     }
 
-    pub fn complete_appointment(env: Env, appointment_id: u64) -> Result<(), Error> {
-         let mut appointments: Map<u64, Appointment> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Appointments)
-            .ok_or(Error::NotInitialized)?;
+    /// Cancels on the doctor's side. Any escrowed fee is refunded to the patient in full.
+    pub fn doctor_cancel_appointment(
+        env: Env,
+        appointment_id: u64,
+        reason: CancellationReason,
+    ) -> Result<(), Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+        appointment.doctor.require_auth();
+
+        let doctor = appointment.doctor.clone();
+        Self::cancel_by_provider(&env, &mut appointment, doctor, CancellationInitiator::Doctor, reason)
+    }
+
+    /// Cancels on behalf of the institution that verified the doctor in
+    /// doctor-credentials. Any escrowed fee is refunded to the patient in full.
+    pub fn institution_cancel_appointment(
+        env: Env,
+        institution: Address,
+        appointment_id: u64,
+        reason: CancellationReason,
+    ) -> Result<(), Error> {
+        institution.require_auth();
+
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+        if credentials::get_institution(&env, &appointment.doctor)? != Some(institution.clone()) {
+            return Err(Error::Unauthorized);
+        }
+
+        Self::cancel_by_provider(
+            &env,
+            &mut appointment,
+            institution,
+            CancellationInitiator::Institution,
+            reason,
+        )
+    }
+
+    /// Shorthand for the `cancellation` of the appointment, if it was cancelled.
+    pub fn get_cancellation(env: Env, appointment_id: u64) -> Option<CancellationRecord> {
+        storage::get_appointment(&env, appointment_id)
+            .ok()
+            .and_then(|appointment| appointment.cancellation_record())
+    }
 
-        let mut appointment = appointments
-            .get(appointment_id)
-            .ok_or(Error::AppointmentNotFound)?;
+    /// Marks the visit as held and releases any escrowed fee to the doctor.
+    pub fn complete_appointment(env: Env, appointment_id: u64) -> Result<(), Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
 
         appointment.doctor.require_auth();
-        if appointment.status != AppointmentStatus::Scheduled {
+        if !appointment.is_active() {
              return Err(Error::InvalidStatus);
         }
-        appointment.status = AppointmentStatus::Completed;
+        Self::complete(&env, &mut appointment);
+        Ok(())
+    }
 
+    /// Completes the appointment and files the consultation summary as a
+    /// medical record of the patient, linking the two. Returns the record ID.
+    pub fn complete_appointment_with_record(
+        env: Env,
+        appointment_id: u64,
+        record_hash: BytesN<32>,
+        description: String,
+    ) -> Result<u32, Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
 
-        appointments.set(appointment_id, appointment.clone());
-        env.storage().persistent().set(&DataKey::Appointments, &appointments);
+        appointment.doctor.require_auth();
+        if !appointment.is_active() {
+            return Err(Error::InvalidStatus);
+        }
+        let record_id = records::add_record(&env, &appointment, &record_hash, &description)?;
+        appointment.medical_record_id = Some(record_id);
+        Self::complete(&env, &mut appointment);
 
-        log!(&env, "Appointment completed: ID={}", appointment_id);
-         Ok(())
+        Ok(record_id)
     }
 
-    pub fn get_appointments(env: Env, user: Address) -> Vec<Appointment> {
-        let appointments: Map<u64, Appointment> = env
-            .storage()
+    /// Lets the patient rate a completed appointment once, with a hash of the
+    /// review text. The score is added to the doctor's reputation.
+    pub fn rate_appointment(
+        env: Env,
+        appointment_id: u64,
+        score: u32,
+        review_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        let appointment = storage::get_appointment(&env, appointment_id)?;
+        appointment.patient.require_auth();
+
+        if appointment.status != AppointmentStatus::Completed {
+            return Err(Error::InvalidStatus);
+        }
+        if !(MIN_RATING..=MAX_RATING).contains(&score) {
+            return Err(Error::InvalidRating);
+        }
+        let key = DataKey::Rating(appointment_id);
+        if env.storage().persistent().has(&key) {
+            return Err(Error::AlreadyRated);
+        }
+
+        env.storage().persistent().set(&key, &AppointmentRating {
+            appointment_id,
+            doctor: appointment.doctor.clone(),
+            score,
+            review_hash,
+            rated_at: env.ledger().timestamp(),
+        });
+
+        let mut reputation = Self::get_doctor_reputation(env.clone(), appointment.doctor.clone());
+        reputation.rating_count += 1;
+        reputation.score_sum += score as u64;
+        reputation.average = (reputation.score_sum * 100 / reputation.rating_count as u64) as u32;
+        env.storage()
             .persistent()
-            .get(&DataKey::Appointments)
-            .unwrap_or_else(|| map![&env]);
+            .set(&DataKey::DoctorReputation(appointment.doctor.clone()), &reputation);
 
-        let mut user_appointments: Vec<Appointment> = vec![&env];
+        log!(&env, "Appointment rated: ID={}, Doctor={}, Score={}",
+            appointment_id, appointment.doctor, score);
+        Ok(())
+    }
 
-        for (_id, appointment) in appointments.iter() {
-            if appointment.patient == user || appointment.doctor == user {
-                user_appointments.push_back(appointment);
-            }
+    pub fn get_rating(env: Env, appointment_id: u64) -> Option<AppointmentRating> {
+        env.storage().persistent().get(&DataKey::Rating(appointment_id))
+    }
+
+    pub fn get_doctor_reputation(env: Env, doctor: Address) -> DoctorReputation {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DoctorReputation(doctor))
+            .unwrap_or_default()
+    }
+
+    /// Records the patient's arrival. Allowed from `CHECK_IN_OPENS_BEFORE` before
+    /// the start time until `CHECK_IN_CLOSES_AFTER` after it.
+    pub fn check_in(env: Env, appointment_id: u64) -> Result<(), Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+        appointment.patient.require_auth();
+
+        if appointment.status != AppointmentStatus::Scheduled {
+            return Err(Error::InvalidStatus);
         }
-        user_appointments
+        let now = env.ledger().timestamp();
+        let opens = appointment.datetime.saturating_sub(CHECK_IN_OPENS_BEFORE);
+        let closes = appointment.datetime + CHECK_IN_CLOSES_AFTER;
+        if now < opens || now > closes {
+            return Err(Error::OutsideCheckInWindow);
+        }
+
+        storage::set_status(&env, &mut appointment, AppointmentStatus::CheckedIn, now);
+        env.storage().persistent().remove(&DataKey::PendingReschedule(appointment_id));
+        storage::save_appointment(&env, &appointment);
+
+        log!(&env, "Patient checked in: ID={}", appointment_id);
+        Ok(())
     }
 
-    pub fn get_appointment_details(env: Env, appointment_id: u64) -> Result<Appointment, Error> {
-         let appointments: Map<u64, Appointment> = env
+    /// Records that `participant`, the doctor or the patient, joined the
+    /// telemedicine session. Allowed from `SESSION_OPENS_BEFORE` before the
+    /// start time until the scheduled end.
+    pub fn join_session(env: Env, appointment_id: u64, participant: Address) -> Result<(), Error> {
+        participant.require_auth();
+
+        let appointment = storage::get_appointment(&env, appointment_id)?;
+        if !matches!(appointment.details.modality, Modality::Telemedicine(_)) {
+            return Err(Error::NotTelemedicine);
+        }
+        if !appointment.is_active() {
+            return Err(Error::InvalidStatus);
+        }
+        let now = env.ledger().timestamp();
+        let opens = appointment.datetime.saturating_sub(SESSION_OPENS_BEFORE);
+        if now < opens || now > appointment.end_time() {
+            return Err(Error::OutsideSessionWindow);
+        }
+
+        let mut session = Self::get_session(env.clone(), appointment_id).unwrap_or_default();
+        if session.ended_at.is_some() {
+            return Err(Error::SessionEnded);
+        }
+        let joined_at = if participant == appointment.doctor {
+            &mut session.doctor_joined_at
+        } else if participant == appointment.patient {
+            &mut session.patient_joined_at
+        } else {
+            return Err(Error::Unauthorized);
+        };
+        if joined_at.is_some() {
+            return Err(Error::AlreadyJoined);
+        }
+        *joined_at = Some(now);
+        env.storage().persistent().set(&DataKey::Session(appointment_id), &session);
+
+        log!(&env, "Session joined: ID={}, By={}", appointment_id, participant);
+        Ok(())
+    }
+
+    /// Closes the session. Either participant may end it once they have joined.
+    pub fn end_session(env: Env, appointment_id: u64, participant: Address) -> Result<(), Error> {
+        participant.require_auth();
+
+        let appointment = storage::get_appointment(&env, appointment_id)?;
+        let mut session = Self::get_session(env.clone(), appointment_id)
+            .ok_or(Error::SessionNotJoined)?;
+        let joined_at = if participant == appointment.doctor {
+            session.doctor_joined_at
+        } else if participant == appointment.patient {
+            session.patient_joined_at
+        } else {
+            return Err(Error::Unauthorized);
+        };
+        if joined_at.is_none() {
+            return Err(Error::SessionNotJoined);
+        }
+        if session.ended_at.is_some() {
+            return Err(Error::SessionEnded);
+        }
+        session.ended_at = Some(env.ledger().timestamp());
+        env.storage().persistent().set(&DataKey::Session(appointment_id), &session);
+
+        log!(&env, "Session ended: ID={}, By={}", appointment_id, participant);
+        Ok(())
+    }
+
+    pub fn get_session(env: Env, appointment_id: u64) -> Option<TelemedicineSession> {
+        env.storage().persistent().get(&DataKey::Session(appointment_id))
+    }
+
+    /// Attaches the hash of an intake document to an upcoming appointment.
+    /// Attachments lock once the appointment is no longer active.
+    pub fn add_attachment(
+        env: Env,
+        appointment_id: u64,
+        kind: AttachmentKind,
+        document_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        let appointment = storage::get_appointment(&env, appointment_id)?;
+        appointment.patient.require_auth();
+
+        if !appointment.is_active() {
+            return Err(Error::InvalidStatus);
+        }
+        if document_hash.to_array() == [0u8; 32] {
+            return Err(Error::InvalidAttachment);
+        }
+
+        let key = DataKey::Attachments(appointment_id);
+        let mut attachments: Vec<Attachment> =
+            env.storage().persistent().get(&key).unwrap_or_else(|| vec![&env]);
+        attachments.push_back(Attachment {
+            kind,
+            document_hash,
+            attached_at: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&key, &attachments);
+
+        log!(&env, "Attachment added: ID={}, Count={}", appointment_id, attachments.len());
+        Ok(())
+    }
+
+    /// Lists the attachments of an appointment. Only its doctor may call this.
+    pub fn get_attachments(
+        env: Env,
+        appointment_id: u64,
+        caller: Address,
+    ) -> Result<Vec<Attachment>, Error> {
+        caller.require_auth();
+
+        let appointment = storage::get_appointment(&env, appointment_id)?;
+        if caller != appointment.doctor {
+            return Err(Error::Unauthorized);
+        }
+        Ok(env
             .storage()
             .persistent()
-            .get(&DataKey::Appointments)
-            .ok_or(Error::NotInitialized)?;
-        appointments.get(appointment_id).ok_or(Error::AppointmentNotFound)
+            .get(&DataKey::Attachments(appointment_id))
+            .unwrap_or_else(|| vec![&env]))
+    }
+
+    /// Lets the doctor record that the patient did not show up, once
+    /// `NO_SHOW_GRACE_PERIOD` has passed since the start without a check-in.
+    /// Any escrowed fee goes to the doctor.
+    pub fn mark_no_show(env: Env, appointment_id: u64) -> Result<(), Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+        appointment.doctor.require_auth();
+
+        if appointment.status != AppointmentStatus::Scheduled {
+            return Err(Error::InvalidStatus);
+        }
+        let now = env.ledger().timestamp();
+        if now < appointment.datetime + NO_SHOW_GRACE_PERIOD {
+            return Err(Error::GracePeriodActive);
+        }
+
+        storage::set_status(&env, &mut appointment, AppointmentStatus::NoShow, now);
+        env.storage().persistent().remove(&DataKey::PendingReschedule(appointment_id));
+        storage::save_appointment(&env, &appointment);
+        storage::remove_from_schedule(&env, &appointment);
+        payments::release(&env, &appointment);
+        stats::record_no_show(&env, &appointment.doctor);
+
+        log!(&env, "Appointment marked no-show: ID={}", appointment_id);
+        Ok(())
+    }
+
+    /// Closes an appointment that is still `Scheduled` `EXPIRY_PERIOD` after its
+    /// end. Callable by anyone. Any escrowed fee goes back to the patient.
+    pub fn expire_appointment(env: Env, appointment_id: u64) -> Result<(), Error> {
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+
+        if appointment.status != AppointmentStatus::Scheduled {
+            return Err(Error::InvalidStatus);
+        }
+        let now = env.ledger().timestamp();
+        if now < appointment.end_time() + EXPIRY_PERIOD {
+            return Err(Error::GracePeriodActive);
+        }
+
+        storage::set_status(&env, &mut appointment, AppointmentStatus::Expired, now);
+        env.storage().persistent().remove(&DataKey::PendingReschedule(appointment_id));
+        storage::save_appointment(&env, &appointment);
+        storage::remove_from_schedule(&env, &appointment);
+        payments::refund(&env, &appointment);
+
+        log!(&env, "Appointment expired: ID={}", appointment_id);
+        Ok(())
+    }
+
+    /// Names a guardian who may manage `patient`'s delegates. Replaces any previous one.
+    pub fn set_guardian(env: Env, patient: Address, guardian: Address) {
+        patient.require_auth();
+        env.storage().persistent().set(&DataKey::Guardian(patient.clone()), &guardian);
+
+        log!(&env, "Guardian set: Patient={}, Guardian={}", patient, guardian);
+    }
+
+    pub fn remove_guardian(env: Env, patient: Address) {
+        patient.require_auth();
+        env.storage().persistent().remove(&DataKey::Guardian(patient));
+    }
+
+    pub fn get_guardian(env: Env, patient: Address) -> Option<Address> {
+        delegation::get_guardian(&env, &patient)
+    }
+
+    /// Lets `delegate` create, cancel and reschedule appointments for
+    /// `patient`. `authorizer` is the patient or their guardian.
+    pub fn authorize_delegate(
+        env: Env,
+        authorizer: Address,
+        patient: Address,
+        delegate: Address,
+    ) -> Result<(), Error> {
+        delegation::require_manager(&env, &patient, &authorizer)?;
+
+        let mut delegates = delegation::get_delegates(&env, &patient);
+        if delegate == patient || delegates.contains(&delegate) {
+            return Err(Error::AlreadyDelegated);
+        }
+        delegates.push_back(delegate.clone());
+        delegation::save_delegates(&env, &patient, &delegates);
+
+        log!(&env, "Delegate authorized: Patient={}, Delegate={}, By={}",
+            patient, delegate, authorizer);
+        Ok(())
+    }
+
+    pub fn revoke_delegate(
+        env: Env,
+        authorizer: Address,
+        patient: Address,
+        delegate: Address,
+    ) -> Result<(), Error> {
+        delegation::require_manager(&env, &patient, &authorizer)?;
+
+        let mut delegates = delegation::get_delegates(&env, &patient);
+        let index = delegates.first_index_of(&delegate).ok_or(Error::DelegateNotFound)?;
+        delegates.remove(index);
+        delegation::save_delegates(&env, &patient, &delegates);
+
+        log!(&env, "Delegate revoked: Patient={}, Delegate={}, By={}",
+            patient, delegate, authorizer);
+        Ok(())
+    }
+
+    pub fn get_delegates(env: Env, patient: Address) -> Vec<Address> {
+        delegation::get_delegates(&env, &patient)
+    }
+
+    /// Puts `patient` on `doctor`'s waitlist for any slot starting between
+    /// `earliest` and `latest`.
+    pub fn join_waitlist(
+        env: Env,
+        patient: Address,
+        doctor: Address,
+        earliest: u64,
+        latest: u64,
+    ) -> Result<(), Error> {
+        patient.require_auth();
+
+        if earliest >= latest || latest <= env.ledger().timestamp() {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        let mut entries = waitlist::get_waitlist(&env, &doctor);
+        if entries.iter().any(|entry| entry.patient == patient) {
+            return Err(Error::AlreadyOnWaitlist);
+        }
+        entries.push_back(WaitlistEntry {
+            patient: patient.clone(),
+            earliest,
+            latest,
+            joined_at: env.ledger().timestamp(),
+        });
+        waitlist::save_waitlist(&env, &doctor, &entries);
+
+        log!(&env, "Waitlist joined: Patient={}, Doctor={}", patient, doctor);
+        Ok(())
+    }
+
+    pub fn leave_waitlist(env: Env, patient: Address, doctor: Address) -> Result<(), Error> {
+        patient.require_auth();
+
+        let mut entries = waitlist::get_waitlist(&env, &doctor);
+        let index = entries
+            .iter()
+            .position(|entry| entry.patient == patient)
+            .ok_or(Error::NotOnWaitlist)?;
+        entries.remove(index as u32);
+        waitlist::save_waitlist(&env, &doctor, &entries);
+
+        log!(&env, "Waitlist left: Patient={}, Doctor={}", patient, doctor);
+        Ok(())
+    }
+
+    pub fn get_waitlist(env: Env, doctor: Address) -> Vec<WaitlistEntry> {
+        waitlist::get_waitlist(&env, &doctor)
+    }
+
+    /// Books the offered slot for the waitlisted patient it was offered to,
+    /// with their own visit details. The visit may not run longer than the
+    /// freed slot.
+    pub fn claim_waitlist_offer(env: Env, offer_id: u64, details: VisitDetails) -> Result<u64, Error> {
+        let offer = waitlist::get_offer(&env, offer_id)?;
+        offer.patient.require_auth();
+
+        if env.ledger().timestamp() >= offer.expires_at {
+            return Err(Error::OfferExpired);
+        }
+        validate_visit_details(&details)?;
+        if details.duration > offer.details.duration {
+            return Err(Error::InvalidVisitDetails);
+        }
+
+        // Release the hold first so the booking does not collide with its own offer
+        waitlist::remove_offer(&env, &offer);
+        Self::book(
+            &env,
+            &offer.patient,
+            &offer.patient,
+            &offer.doctor,
+            offer.datetime,
+            details,
+            Priority::Routine,
+            None,
+        )
+    }
+
+    /// Turns down an offer; the slot goes to the next matching patient.
+    pub fn decline_waitlist_offer(env: Env, offer_id: u64) -> Result<(), Error> {
+        let offer = waitlist::get_offer(&env, offer_id)?;
+        offer.patient.require_auth();
+
+        Self::pass_offer(&env, &offer);
+        Ok(())
+    }
+
+    /// Passes an offer that was not claimed in time to the next matching
+    /// patient. Callable by anyone once the offer has expired.
+    pub fn expire_waitlist_offer(env: Env, offer_id: u64) -> Result<(), Error> {
+        let offer = waitlist::get_offer(&env, offer_id)?;
+
+        if env.ledger().timestamp() < offer.expires_at {
+            return Err(Error::OfferNotExpired);
+        }
+
+        Self::pass_offer(&env, &offer);
+        Ok(())
+    }
+
+    pub fn get_waitlist_offer(env: Env, offer_id: u64) -> Result<SlotOffer, Error> {
+        waitlist::get_offer(&env, offer_id)
+    }
+
+    /// Schedules a session in which `doctor` sees up to `seats` patients at
+    /// once. It takes the slot like a regular appointment and counts as one
+    /// booking toward the doctor's daily capacity.
+    pub fn create_group_session(
+        env: Env,
+        doctor: Address,
+        datetime: u64,
+        details: VisitDetails,
+        seats: u32,
+    ) -> Result<u64, SorobanError> {
+        doctor.require_auth();
+
+        if seats == 0 {
+            return Err(GroupError::InvalidSeats.into());
+        }
+        validate_visit_details(&details)?;
+        credentials::require_verified_doctor(&env, &doctor)?;
+        Self::check_slot_free(&env, &doctor, datetime, details.duration, Priority::Routine, None)?;
+
+        let session_id = group::next_session_id(&env);
+        group::insert_session(&env, &GroupSession {
+            id: session_id,
+            doctor: doctor.clone(),
+            datetime,
+            details,
+            seats,
+            attendees: vec![&env],
+            checked_in: vec![&env],
+            status: AppointmentStatus::Scheduled,
+            created_at: env.ledger().timestamp(),
+            completed_at: None,
+        });
+        storage::count_booking(&env, &doctor, datetime);
+
+        log!(&env, "Group session created: ID={}, Doctor={}, Seats={}", session_id, doctor, seats);
+        Ok(session_id)
+    }
+
+    /// Takes a seat in a group session that has not started yet. The doctor's
+    /// consultation fee, if any, is escrowed from the patient.
+    pub fn join_group_session(env: Env, session_id: u64, patient: Address) -> Result<(), GroupError> {
+        patient.require_auth();
+
+        let mut session = group::get_session(&env, session_id)?;
+        if session.status != AppointmentStatus::Scheduled {
+            return Err(GroupError::InvalidStatus);
+        }
+        if session.datetime <= env.ledger().timestamp() {
+            return Err(GroupError::PastTimestamp);
+        }
+        if session.attendees.contains(&patient) {
+            return Err(GroupError::AlreadyInSession);
+        }
+        if session.attendees.len() >= session.seats {
+            return Err(GroupError::SessionFull);
+        }
+        session.attendees.push_back(patient.clone());
+        group::save_session(&env, &session);
+        group::add_patient_session(&env, &patient, session_id);
+        payments::escrow_seat_fee(&env, &session, &patient);
+
+        log!(&env, "Group session joined: ID={}, Patient={}", session_id, patient);
+        Ok(())
+    }
+
+    /// Gives up a seat before the session starts. The seat fee is refunded
+    /// minus any late-cancellation penalty.
+    pub fn leave_group_session(env: Env, session_id: u64, patient: Address) -> Result<(), GroupError> {
+        patient.require_auth();
+
+        let mut session = group::get_session(&env, session_id)?;
+        if session.status != AppointmentStatus::Scheduled {
+            return Err(GroupError::InvalidStatus);
+        }
+        if session.datetime <= env.ledger().timestamp() {
+            return Err(GroupError::PastTimestamp);
+        }
+        let index = session.attendees.first_index_of(&patient).ok_or(GroupError::NotInSession)?;
+        session.attendees.remove(index);
+        group::save_session(&env, &session);
+        payments::refund_seat_cancellation(&env, &session, &patient);
+
+        log!(&env, "Group session left: ID={}, Patient={}", session_id, patient);
+        Ok(())
+    }
+
+    /// Records an attendee's arrival, within the same window as `check_in`.
+    pub fn check_in_group_session(env: Env, session_id: u64, patient: Address) -> Result<(), GroupError> {
+        patient.require_auth();
+
+        let mut session = group::get_session(&env, session_id)?;
+        if session.status != AppointmentStatus::Scheduled {
+            return Err(GroupError::InvalidStatus);
+        }
+        if !session.attendees.contains(&patient) {
+            return Err(GroupError::NotInSession);
+        }
+        if session.checked_in.contains(&patient) {
+            return Err(GroupError::AlreadyCheckedIn);
+        }
+        let now = env.ledger().timestamp();
+        let opens = session.datetime.saturating_sub(CHECK_IN_OPENS_BEFORE);
+        let closes = session.datetime + CHECK_IN_CLOSES_AFTER;
+        if now < opens || now > closes {
+            return Err(GroupError::OutsideCheckInWindow);
+        }
+        session.checked_in.push_back(patient.clone());
+        group::save_session(&env, &session);
+
+        log!(&env, "Group session check-in: ID={}, Patient={}", session_id, patient);
+        Ok(())
+    }
+
+    /// Completes the session for every attendee at once and releases their
+    /// seat fees to the doctor.
+    pub fn complete_group_session(env: Env, session_id: u64) -> Result<(), GroupError> {
+        let mut session = group::get_session(&env, session_id)?;
+        session.doctor.require_auth();
+
+        if session.status != AppointmentStatus::Scheduled {
+            return Err(GroupError::InvalidStatus);
+        }
+        session.status = AppointmentStatus::Completed;
+        session.completed_at = Some(env.ledger().timestamp());
+        group::save_session(&env, &session);
+        group::remove_from_schedule(&env, &session);
+        for patient in session.attendees.iter() {
+            payments::release_seat(&env, &session, &patient);
+        }
+
+        log!(&env, "Group session completed: ID={}, Attended={}",
+            session_id, session.checked_in.len());
+        Ok(())
+    }
+
+    /// Lets the doctor call off a scheduled session. Its slot and daily
+    /// capacity are freed and every attendee's seat fee is refunded in full.
+    pub fn cancel_group_session(env: Env, session_id: u64) -> Result<(), GroupError> {
+        let mut session = group::get_session(&env, session_id)?;
+        session.doctor.require_auth();
+
+        if session.status != AppointmentStatus::Scheduled {
+            return Err(GroupError::InvalidStatus);
+        }
+        session.status = AppointmentStatus::Canceled;
+        group::save_session(&env, &session);
+        group::remove_from_schedule(&env, &session);
+        storage::uncount_booking(&env, &session.doctor, session.datetime);
+        for patient in session.attendees.iter() {
+            payments::refund_seat(&env, &session, &patient);
+        }
+
+        log!(&env, "Group session canceled: ID={}, Attendees={}",
+            session_id, session.attendees.len());
+        Ok(())
+    }
+
+    pub fn get_group_session(env: Env, session_id: u64) -> Result<GroupSession, GroupError> {
+        group::get_session(&env, session_id)
+    }
+
+    /// Every group session `patient` has joined, including ones they left.
+    pub fn get_patient_group_sessions(env: Env, patient: Address) -> Vec<GroupSession> {
+        let mut sessions: Vec<GroupSession> = vec![&env];
+        for id in group::get_patient_session_ids(&env, &patient).iter() {
+            if let Ok(session) = group::get_session(&env, id) {
+                sessions.push_back(session);
+            }
+        }
+        sessions
+    }
+
+    /// Offers future scheduled appointments of `from_doctor` to `to_doctor`, a
+    /// colleague verified by the same institution. `appointment_ids` lists
+    /// them explicitly, at most `MAX_PAGE_SIZE` per handover; longer absences
+    /// take several handovers. `caller` is `from_doctor` or their institution.
+    pub fn propose_handover(
+        env: Env,
+        caller: Address,
+        from_doctor: Address,
+        to_doctor: Address,
+        appointment_ids: Vec<u64>,
+    ) -> Result<u64, Error> {
+        caller.require_auth();
+
+        let institution = credentials::get_institution(&env, &from_doctor)?;
+        if caller != from_doctor && institution != Some(caller.clone()) {
+            return Err(Error::Unauthorized);
+        }
+        // Only a colleague verified by the same institution may cover
+        if institution.is_none()
+            || from_doctor == to_doctor
+            || credentials::get_institution(&env, &to_doctor)? != institution
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        if appointment_ids.is_empty() || appointment_ids.len() > MAX_PAGE_SIZE {
+            return Err(Error::InvalidPagination);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut handed_over: Vec<u64> = vec![&env];
+        for appointment_id in appointment_ids.iter() {
+            if handed_over.contains(appointment_id) {
+                continue;
+            }
+            let appointment = storage::get_appointment(&env, appointment_id)?;
+            if appointment.doctor != from_doctor {
+                return Err(Error::Unauthorized);
+            }
+            if appointment.status != AppointmentStatus::Scheduled || appointment.datetime <= now {
+                return Err(Error::InvalidStatus);
+            }
+            handed_over.push_back(appointment_id);
+        }
+
+        let handover_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextHandoverId)
+            .unwrap_or(1);
+        env.storage().instance().set(&DataKey::NextHandoverId, &(handover_id + 1));
+
+        env.storage().persistent().set(&DataKey::Handover(handover_id), &Handover {
+            id: handover_id,
+            from_doctor: from_doctor.clone(),
+            to_doctor: to_doctor.clone(),
+            proposed_by: caller,
+            appointment_ids: handed_over,
+            proposed_at: now,
+            accepted_at: None,
+        });
+
+        log!(&env, "Handover proposed: ID={}, From={}, To={}", handover_id, from_doctor, to_doctor);
+        Ok(handover_id)
+    }
+
+    /// Lets the covering doctor take over the appointments of the handover
+    /// that are still scheduled. Each one must fit the covering doctor's
+    /// published availability as a new booking would. Each patient gets a
+    /// `handover` event and may still decline with `decline_handover`.
+    pub fn accept_handover(env: Env, handover_id: u64) -> Result<(), Error> {
+        let mut handover = Self::get_handover(env.clone(), handover_id)?;
+        handover.to_doctor.require_auth();
+
+        if handover.accepted_at.is_some() {
+            return Err(Error::InvalidStatus);
+        }
+
+        let now = env.ledger().timestamp();
+        for appointment_id in handover.appointment_ids.iter() {
+            let mut appointment = storage::get_appointment(&env, appointment_id)?;
+            if appointment.status != AppointmentStatus::Scheduled
+                || appointment.doctor != handover.from_doctor
+                || appointment.datetime <= now
+            {
+                continue;
+            }
+            Self::check_slot_free(
+                &env,
+                &handover.to_doctor,
+                appointment.datetime,
+                appointment.details.duration,
+                appointment.priority,
+                None,
+            )?;
+
+            storage::reassign_doctor(&env, &mut appointment, &handover.to_doctor);
+            stats::move_booking(
+                &env,
+                &handover.from_doctor,
+                &handover.to_doctor,
+                appointment.booking_lead_time(),
+            );
+            env.storage().persistent().remove(&DataKey::PendingReschedule(appointment_id));
+            env.events().publish(
+                (symbol_short!("handover"), appointment.patient.clone()),
+                (appointment_id, handover.to_doctor.clone()),
+            );
+        }
+
+        handover.accepted_at = Some(now);
+        env.storage().persistent().set(&DataKey::Handover(handover_id), &handover);
+
+        log!(&env, "Handover accepted: ID={}, By={}", handover_id, handover.to_doctor);
+        Ok(())
+    }
+
+    /// Lets the patient of an appointment moved by an accepted handover refuse
+    /// the covering doctor. The appointment is canceled on behalf of whoever
+    /// proposed the handover and any escrowed fee refunded in full.
+    pub fn decline_handover(env: Env, handover_id: u64, appointment_id: u64) -> Result<(), Error> {
+        let handover = Self::get_handover(env.clone(), handover_id)?;
+        if !handover.appointment_ids.contains(appointment_id) {
+            return Err(Error::AppointmentNotFound);
+        }
+
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+        appointment.patient.require_auth();
+
+        if handover.accepted_at.is_none()
+            || appointment.doctor != handover.to_doctor
+            || appointment.status != AppointmentStatus::Scheduled
+        {
+            return Err(Error::InvalidStatus);
+        }
+
+        let initiator = if handover.proposed_by == handover.from_doctor {
+            CancellationInitiator::Doctor
+        } else {
+            CancellationInitiator::Institution
+        };
+        Self::cancel(
+            &env,
+            &mut appointment,
+            handover.proposed_by,
+            initiator,
+            CancellationReason::DoctorUnavailable,
+        );
+        payments::refund(&env, &appointment);
+        Ok(())
+    }
+
+    pub fn get_handover(env: Env, handover_id: u64) -> Result<Handover, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Handover(handover_id))
+            .ok_or(Error::HandoverNotFound)
+    }
+
+    /// Suggests moving a scheduled appointment to `new_datetime`. The appointment
+    /// only moves once the other party calls `accept_reschedule`.
+    pub fn propose_reschedule(
+        env: Env,
+        appointment_id: u64,
+        proposer: Address,
+        new_datetime: u64,
+    ) -> Result<(), Error> {
+        proposer.require_auth();
+
+        let appointment = storage::get_appointment(&env, appointment_id)?;
+
+        if proposer != appointment.doctor
+            && !delegation::acts_for(&env, &appointment.patient, &proposer)
+        {
+            return Err(Error::Unauthorized);
+        }
+        if appointment.status != AppointmentStatus::Scheduled {
+            return Err(Error::InvalidStatus);
+        }
+        let key = DataKey::PendingReschedule(appointment_id);
+        if env.storage().persistent().has(&key) {
+            return Err(Error::ReschedulePending);
+        }
+        Self::check_slot_free(
+            &env,
+            &appointment.doctor,
+            new_datetime,
+            appointment.details.duration,
+            appointment.priority,
+            Some(appointment_id),
+        )?;
+
+        let proposal = RescheduleProposal {
+            proposed_by: proposer.clone(),
+            new_datetime,
+            proposed_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &proposal);
+
+        log!(&env, "Reschedule proposed: ID={}, By={}, Time={}",
+            appointment_id, proposer, new_datetime);
+        Ok(())
+    }
+
+    /// Accepts the pending proposal. Only the party that did not propose it may accept.
+    pub fn accept_reschedule(env: Env, appointment_id: u64, approver: Address) -> Result<(), Error> {
+        approver.require_auth();
+
+        let mut appointment = storage::get_appointment(&env, appointment_id)?;
+
+        if appointment.status != AppointmentStatus::Scheduled {
+            return Err(Error::InvalidStatus);
+        }
+        let key = DataKey::PendingReschedule(appointment_id);
+        let proposal: RescheduleProposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NoPendingReschedule)?;
+
+        // Patient-side proposals need the doctor and the other way round
+        let approver_is_doctor = approver == appointment.doctor;
+        let is_party = approver_is_doctor
+            || delegation::acts_for(&env, &appointment.patient, &approver);
+        if !is_party || approver_is_doctor == (proposal.proposed_by == appointment.doctor) {
+            return Err(Error::Unauthorized);
+        }
+        Self::check_slot_free(
+            &env,
+            &appointment.doctor,
+            proposal.new_datetime,
+            appointment.details.duration,
+            appointment.priority,
+            Some(appointment_id),
+        )?;
+
+        appointment.reschedule_history.push_back(RescheduleRecord {
+            previous_datetime: appointment.datetime,
+            new_datetime: proposal.new_datetime,
+            proposed_by: proposal.proposed_by,
+            approved_by: approver,
+            rescheduled_at: env.ledger().timestamp(),
+        });
+        storage::move_appointment(&env, &mut appointment, proposal.new_datetime);
+        env.storage().persistent().remove(&key);
+
+        log!(&env, "Appointment rescheduled: ID={}, Time={}", appointment_id, proposal.new_datetime);
+        Ok(())
+    }
+
+    /// Drops the pending proposal. The other party declines it, or the proposer withdraws it.
+    pub fn decline_reschedule(env: Env, appointment_id: u64, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        let appointment = storage::get_appointment(&env, appointment_id)?;
+
+        if caller != appointment.doctor && !delegation::acts_for(&env, &appointment.patient, &caller) {
+            return Err(Error::Unauthorized);
+        }
+        let key = DataKey::PendingReschedule(appointment_id);
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::NoPendingReschedule);
+        }
+        env.storage().persistent().remove(&key);
+
+        log!(&env, "Reschedule declined: ID={}, By={}", appointment_id, caller);
+        Ok(())
+    }
+
+    pub fn get_pending_reschedule(env: Env, appointment_id: u64) -> Option<RescheduleProposal> {
+        env.storage().persistent().get(&DataKey::PendingReschedule(appointment_id))
+    }
+
+    pub fn get_appointments(env: Env, user: Address) -> Vec<Appointment> {
+        let mut user_appointments: Vec<Appointment> = vec![&env];
+
+        for id in storage::get_user_appointment_ids(&env, &user).iter() {
+            if let Ok(appointment) = storage::get_appointment(&env, id) {
+                user_appointments.push_back(appointment);
+            }
+        }
+        user_appointments
+    }
+
+    /// Returns up to `limit` of `user`'s appointments starting at position
+    /// `cursor` of their index, in booking order. Pass the returned
+    /// `next_cursor` to fetch the following page.
+    pub fn get_appointments_page(
+        env: Env,
+        user: Address,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<AppointmentPage, Error> {
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(Error::InvalidPagination);
+        }
+
+        let ids = storage::get_user_appointment_ids(&env, &user);
+        let end = ids.len().min(cursor.saturating_add(limit));
+
+        let mut appointments: Vec<Appointment> = vec![&env];
+        for index in cursor..end {
+            let id = ids.get_unchecked(index);
+            if let Ok(appointment) = storage::get_appointment(&env, id) {
+                appointments.push_back(appointment);
+            }
+        }
+
+        let next_cursor = if end < ids.len() { Some(end) } else { None };
+        Ok(AppointmentPage { appointments, next_cursor })
+    }
+
+    /// Returns `user`'s appointments whose start falls in `[from, to)`.
+    pub fn get_appointments_between(
+        env: Env,
+        user: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<Appointment>, Error> {
+        if from >= to {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        let mut appointments: Vec<Appointment> = vec![&env];
+        for id in storage::get_user_appointment_ids(&env, &user).iter() {
+            if let Ok(appointment) = storage::get_appointment(&env, id)
+                && appointment.datetime >= from
+                && appointment.datetime < to
+            {
+                appointments.push_back(appointment);
+            }
+        }
+        Ok(appointments)
+    }
+
+    pub fn get_appointment_details(env: Env, appointment_id: u64) -> Result<Appointment, Error> {
+        storage::get_appointment(&env, appointment_id)
+    }
+
+    pub fn get_payment(env: Env, appointment_id: u64) -> Option<Payment> {
+        payments::get_payment(&env, appointment_id)
+    }
+
+    /// The seat fee `patient` paid for group session `session_id`, if any.
+    pub fn get_seat_payment(env: Env, session_id: u64, patient: Address) -> Option<Payment> {
+        payments::get_seat_payment(&env, session_id, &patient)
+    }
+
+    /// Utilisation counters of `doctor`, maintained as appointments change
+    /// status. Lead times are in seconds.
+    pub fn get_doctor_stats(env: Env, doctor: Address) -> DoctorStats {
+        stats::get_stats(&env, &doctor)
+    }
+}
+
+impl AppointmentSchedulingContract {
+    /// Shared booking path once the caller has been authorized for `patient`.
+    #[allow(clippy::too_many_arguments)]
+    fn book(
+        env: &Env,
+        patient: &Address,
+        booked_by: &Address,
+        doctor: &Address,
+        datetime: u64,
+        details: VisitDetails,
+        priority: Priority,
+        series_id: Option<u64>,
+    ) -> Result<u64, Error> {
+        validate_visit_details(&details)?;
+        credentials::require_verified_doctor(env, doctor)?;
+        Self::check_slot_free(env, doctor, datetime, details.duration, priority, None)?;
+        if let Some(limit) = Self::get_patient_appointment_limit(env.clone())
+            && storage::get_open_count(env, patient) >= limit
+        {
+            return Err(Error::PatientLimitReached);
+        }
+
+        let appointment_id = storage::next_appointment_id(env)?;
+        let new_appointment = Appointment {
+            id: appointment_id,
+            patient: patient.clone(),
+            doctor: doctor.clone(),
+            datetime,
+            details,
+            series_id,
+            booked_by: booked_by.clone(),
+            priority,
+            status: AppointmentStatus::Scheduled,
+            status_history: vec![env, StatusChange {
+                status: AppointmentStatus::Scheduled,
+                changed_at: env.ledger().timestamp(),
+            }],
+            reschedule_history: vec![env],
+            medical_record_id: None,
+            cancellation: Cancellation::NotCancelled,
+        };
+
+        storage::insert_appointment(env, &new_appointment);
+        payments::escrow_fee(env, &new_appointment);
+        stats::record_booking(env, doctor, datetime - env.ledger().timestamp());
+
+        log!(env, "Appointment created: ID={}, Patient={}, Doctor={}, Time={}, By={}",
+            appointment_id, patient, doctor, datetime, booked_by);
+
+        Ok(appointment_id)
+    }
+
+    fn complete(env: &Env, appointment: &mut Appointment) {
+        storage::set_status(env, appointment, AppointmentStatus::Completed, env.ledger().timestamp());
+        env.storage().persistent().remove(&DataKey::PendingReschedule(appointment.id));
+
+        storage::save_appointment(env, appointment);
+        storage::remove_from_schedule(env, appointment);
+        payments::release(env, appointment);
+        stats::record_completion(env, &appointment.doctor);
+
+        log!(env, "Appointment completed: ID={}", appointment.id);
+    }
+
+    fn cancel(
+        env: &Env,
+        appointment: &mut Appointment,
+        cancelled_by: Address,
+        initiator: CancellationInitiator,
+        reason: CancellationReason,
+    ) {
+        let now = env.ledger().timestamp();
+        storage::set_status(env, appointment, AppointmentStatus::Canceled, now);
+        appointment.cancellation = Cancellation::Cancelled(CancellationRecord {
+            cancelled_by: cancelled_by.clone(),
+            initiator,
+            reason,
+            cancelled_at: now,
+        });
+        storage::save_appointment(env, appointment);
+        storage::remove_from_schedule(env, appointment);
+        storage::uncount_booking(env, &appointment.doctor, appointment.datetime);
+
+        env.storage().persistent().remove(&DataKey::PendingReschedule(appointment.id));
+        stats::record_cancellation(env, &appointment.doctor, initiator);
+
+        log!(env, "Appointment canceled: ID={}, By={}", appointment.id, cancelled_by);
+    }
+
+    fn cancel_by_patient(env: &Env, appointment: &mut Appointment, cancelled_by: Address) {
+        Self::cancel(
+            env,
+            appointment,
+            cancelled_by,
+            CancellationInitiator::Patient,
+            CancellationReason::PatientRequest,
+        );
+        payments::refund_cancellation(env, appointment);
+        waitlist::offer_slot(env, &appointment.doctor, appointment.datetime, &appointment.details);
+    }
+
+    fn cancel_by_provider(
+        env: &Env,
+        appointment: &mut Appointment,
+        cancelled_by: Address,
+        initiator: CancellationInitiator,
+        reason: CancellationReason,
+    ) -> Result<(), Error> {
+        if matches!(reason, CancellationReason::PatientRequest | CancellationReason::Displaced) {
+            return Err(Error::InvalidCancellationReason);
+        }
+        if !appointment.is_active() {
+            return Err(Error::InvalidStatus);
+        }
+
+        Self::cancel(env, appointment, cancelled_by, initiator, reason);
+        payments::refund(env, appointment);
+        Ok(())
+    }
+
+    fn displace(env: &Env, appointment: &mut Appointment, staff: &Address) {
+        Self::cancel(
+            env,
+            appointment,
+            staff.clone(),
+            CancellationInitiator::ClinicStaff,
+            CancellationReason::Displaced,
+        );
+        payments::refund(env, appointment);
+
+        env.events().publish(
+            (symbol_short!("displaced"), appointment.patient.clone()),
+            (appointment.id, appointment.datetime),
+        );
+    }
+
+    fn pass_offer(env: &Env, offer: &SlotOffer) {
+        waitlist::remove_offer(env, offer);
+        waitlist::offer_slot(env, &offer.doctor, offer.datetime, &offer.details);
+
+        log!(env, "Waitlist offer passed on: ID={}", offer.id);
+    }
+
+    fn publish_window(
+        env: &Env,
+        doctor: &Address,
+        start: u64,
+        end: u64,
+        slot_duration: u64,
+        urgent_only: bool,
+    ) -> Result<u64, Error> {
+        if start <= env.ledger().timestamp() {
+            return Err(Error::PastTimestamp);
+        }
+        if slot_duration == 0 || end <= start || end - start < slot_duration {
+            return Err(Error::InvalidAvailability);
+        }
+
+        let mut windows = Self::get_availability(env.clone(), doctor.clone());
+        for window in windows.iter() {
+            if start < window.end && window.start < end {
+                return Err(Error::InvalidAvailability);
+            }
+        }
+
+        let window_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextAvailabilityId)
+            .unwrap_or(1);
+
+        windows.push_back(AvailabilityWindow {
+            id: window_id,
+            start,
+            end,
+            slot_duration,
+            urgent_only,
+        });
+        env.storage().persistent().set(&DataKey::Availability(doctor.clone()), &windows);
+        env.storage().instance().set(&DataKey::NextAvailabilityId, &(window_id + 1));
+
+        log!(env, "Availability added: ID={}, Doctor={}, Start={}, End={}",
+            window_id, doctor, start, end);
+
+        Ok(window_id)
+    }
+
+
+    fn require_admin(env: &Env) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+
+    /// Checks that `datetime` is in the future, starts a published slot of
+    /// `doctor` whose window can hold `duration`, that `duration` spans whole
+    /// slots up to `MAX_SLOTS_PER_VISIT`, and that no other booking overlaps
+    /// it. `exclude` skips the appointment being moved.
+    fn check_slot_free(
+        env: &Env,
+        doctor: &Address,
+        datetime: u64,
+        duration: u64,
+        priority: Priority,
+        exclude: Option<u64>,
+    ) -> Result<(), Error> {
+        if datetime <= env.ledger().timestamp() {
+            return Err(Error::PastTimestamp);
+        }
+
+        let windows = Self::get_availability(env.clone(), doctor.clone());
+        let window = find_slot_window(&windows, datetime, duration).ok_or(Error::SlotUnavailable)?;
+        if !duration.is_multiple_of(window.slot_duration)
+            || duration > MAX_SLOTS_PER_VISIT * window.slot_duration
+        {
+            return Err(Error::InvalidVisitDetails);
+        }
+        if window.urgent_only && priority == Priority::Routine {
+            return Err(Error::UrgentSlotOnly);
+        }
+        if overlaps(&busy_intervals(env, doctor, datetime, datetime + duration, exclude), datetime, duration) {
+            return Err(Error::SlotAlreadyBooked);
+        }
+        if let Some(capacity) = Self::get_daily_capacity(env.clone(), doctor.clone())
+            && doctor_day_count(env, doctor, datetime / SECONDS_PER_DAY, exclude) >= capacity
+        {
+            return Err(Error::DailyCapacityReached);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the published window in which `datetime` is the start of a slot
+/// and which still holds `duration` seconds after it.
+fn find_slot_window(
+    windows: &Vec<AvailabilityWindow>,
+    datetime: u64,
+    duration: u64,
+) -> Option<AvailabilityWindow> {
+    windows.iter().find(|window| {
+        datetime >= window.start
+            && datetime + duration <= window.end
+            && (datetime - window.start).is_multiple_of(window.slot_duration)
+    })
+}
+
+/// Time ranges `doctor` cannot be booked for around `[from, to)`: active
+/// appointments other than `exclude` on the days the range touches, slots
+/// held by waitlist offers and scheduled group sessions.
+fn busy_intervals(env: &Env, doctor: &Address, from: u64, to: u64, exclude: Option<u64>) -> Vec<(u64, u64)> {
+    let mut busy = vec![env];
+    for day in storage::days_spanned(from, to) {
+        for appointment in storage::get_doctor_day_appointments(env, doctor, day).iter() {
+            if Some(appointment.id) != exclude {
+                busy.push_back((appointment.datetime, appointment.end_time()));
+            }
+        }
+        for offer in waitlist::get_active_offers(env, doctor, day).iter() {
+            busy.push_back((offer.datetime, offer.datetime + offer.details.duration));
+        }
+        for session in group::get_doctor_day_sessions(env, doctor, day).iter() {
+            busy.push_back((session.datetime, session.end_time()));
+        }
+    }
+    busy
+}
+
+/// Number of `doctor`'s bookings that have not been canceled on `day`,
+/// counted in days since the epoch. `exclude` leaves out the appointment
+/// being moved.
+fn doctor_day_count(env: &Env, doctor: &Address, day: u64, exclude: Option<u64>) -> u32 {
+    let count = storage::get_day_booking_count(env, doctor, day);
+    match exclude.and_then(|id| storage::get_appointment(env, id).ok()) {
+        Some(appointment) if appointment.doctor == *doctor && appointment.datetime / SECONDS_PER_DAY == day => {
+            count.saturating_sub(1)
+        }
+        _ => count,
+    }
+}
+
+/// Whether `[datetime, datetime + duration)` intersects any of `busy`.
+fn overlaps(busy: &Vec<(u64, u64)>, datetime: u64, duration: u64) -> bool {
+    busy.iter().any(|(start, end)| start < datetime + duration && datetime < end)
+}
+
+fn validate_visit_details(details: &VisitDetails) -> Result<(), Error> {
+    let missing_link = matches!(
+        &details.modality,
+        Modality::Telemedicine(meeting_link_hash) if meeting_link_hash.to_array() == [0u8; 32]
+    );
+    if details.duration == 0 || missing_link {
+        return Err(Error::InvalidVisitDetails);
     }
-}
\ No newline at end of file
+    Ok(())
+}
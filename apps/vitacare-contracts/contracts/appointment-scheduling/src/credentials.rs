@@ -0,0 +1,53 @@
+use soroban_sdk::{contractclient, contracttype, Address, Bytes, Env, String};
+
+use crate::error::Error;
+use crate::storage_types::DataKey;
+
+/// Mirror of `DoctorData` in the doctor-credentials contract.
+#[contracttype]
+#[derive(Clone)]
+pub struct DoctorData {
+    pub name: String,
+    pub specialization: String,
+    pub certificate_hash: Bytes,
+    pub is_verified: bool,
+    pub verified_by: Option<Address>,
+}
+
+/// The subset of the doctor-credentials contract this contract calls.
+#[allow(dead_code)]
+#[contractclient(name = "DoctorCredentialsClient")]
+pub trait DoctorCredentialsInterface {
+    fn get_doctor(env: Env, wallet: Address) -> DoctorData;
+}
+
+/// Look up `doctor` in the configured credentials contract. Returns `None`
+/// when no credentials contract is configured.
+pub fn get_doctor_data(env: &Env, doctor: &Address) -> Result<Option<DoctorData>, Error> {
+    let Some(credentials) = env
+        .storage()
+        .instance()
+        .get::<DataKey, Address>(&DataKey::DoctorCredentialsContract)
+    else {
+        return Ok(None);
+    };
+
+    match DoctorCredentialsClient::new(env, &credentials).try_get_doctor(doctor) {
+        Ok(Ok(data)) => Ok(Some(data)),
+        _ => Err(Error::DoctorNotVerified),
+    }
+}
+
+/// The institution that verified `doctor`, if any.
+pub fn get_institution(env: &Env, doctor: &Address) -> Result<Option<Address>, Error> {
+    Ok(get_doctor_data(env, doctor)?.and_then(|data| data.verified_by))
+}
+
+/// Fails unless `doctor` is registered and verified in the configured
+/// credentials contract. Passes when none is configured.
+pub fn require_verified_doctor(env: &Env, doctor: &Address) -> Result<(), Error> {
+    match get_doctor_data(env, doctor)? {
+        Some(data) if !data.is_verified => Err(Error::DoctorNotVerified),
+        _ => Ok(()),
+    }
+}
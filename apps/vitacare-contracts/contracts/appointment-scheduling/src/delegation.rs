@@ -0,0 +1,42 @@
+use soroban_sdk::{vec, Address, Env, Vec};
+
+use crate::error::Error;
+use crate::storage_types::DataKey;
+
+pub fn get_guardian(env: &Env, patient: &Address) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::Guardian(patient.clone()))
+}
+
+pub fn get_delegates(env: &Env, patient: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Delegates(patient.clone()))
+        .unwrap_or_else(|| vec![env])
+}
+
+pub fn save_delegates(env: &Env, patient: &Address, delegates: &Vec<Address>) {
+    env.storage().persistent().set(&DataKey::Delegates(patient.clone()), delegates);
+}
+
+/// Whether `caller` is `patient` or one of their authorized delegates.
+pub fn acts_for(env: &Env, patient: &Address, caller: &Address) -> bool {
+    caller == patient || get_delegates(env, patient).contains(caller)
+}
+
+/// Require `caller`'s signature and that they may act for `patient`.
+pub fn require_acts_for(env: &Env, patient: &Address, caller: &Address) -> Result<(), Error> {
+    caller.require_auth();
+    if !acts_for(env, patient, caller) {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Require `authorizer`'s signature and that they are `patient` or their guardian.
+pub fn require_manager(env: &Env, patient: &Address, authorizer: &Address) -> Result<(), Error> {
+    authorizer.require_auth();
+    if authorizer != patient && get_guardian(env, patient).as_ref() != Some(authorizer) {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
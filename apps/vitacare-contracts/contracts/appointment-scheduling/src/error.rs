@@ -11,6 +11,64 @@ pub enum Error {
     AppointmentNotFound = 4,
     InvalidStatus = 5,
     PastTimestamp = 6,
+    SlotUnavailable = 7,
+    SlotAlreadyBooked = 8,
+    InvalidAvailability = 9,
+    AvailabilityNotFound = 10,
+    NoPendingReschedule = 11,
+    ReschedulePending = 12,
+    InvalidVisitDetails = 13,
+    InvalidPagination = 14,
+    InvalidTimeRange = 15,
+    OutsideCheckInWindow = 16,
+    GracePeriodActive = 17,
+    InvalidFee = 18,
+    DoctorNotVerified = 19,
+    AlreadyOnWaitlist = 20,
+    NotOnWaitlist = 21,
+    OfferNotFound = 22,
+    OfferExpired = 23,
+    OfferNotExpired = 24,
+    InvalidCancellationReason = 25,
+    InvalidSeries = 26,
+    SeriesNotFound = 27,
+    AlreadyDelegated = 28,
+    DelegateNotFound = 29,
+    MedicalRecordsNotConfigured = 30,
+    MedicalRecordRejected = 31,
+    InvalidRating = 32,
+    AlreadyRated = 33,
+    NotTelemedicine = 34,
+    OutsideSessionWindow = 35,
+    AlreadyJoined = 36,
+    SessionNotJoined = 37,
+    SessionEnded = 38,
+    DailyCapacityReached = 39,
+    PatientLimitReached = 40,
+    InvalidLimit = 41,
+    UrgentSlotOnly = 42,
+    NotClinicStaff = 43,
+    InvalidPriority = 44,
+    HandoverNotFound = 49,
+    InvalidAttachment = 50,
+}
+
+/// Failures specific to group sessions. Codes shared with `Error` keep its
+/// numbers and the others never overlap it.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum GroupError {
+    HostError = 0,
+    InvalidStatus = 5,
+    PastTimestamp = 6,
+    OutsideCheckInWindow = 16,
+    GroupSessionNotFound = 45,
+    SessionFull = 46,
+    NotInSession = 47,
+    InvalidSeats = 48,
+    AlreadyInSession = 51,
+    AlreadyCheckedIn = 52,
 }
 
 impl From<Error> for SorobanError {
@@ -29,4 +87,22 @@ impl From<SorobanError> for Error {
     fn from(_err: SorobanError) -> Error {
         Error::HostError
     }
-}
\ No newline at end of file
+}
+
+impl From<GroupError> for SorobanError {
+    fn from(err: GroupError) -> SorobanError {
+        SorobanError::from_contract_error(err as u32)
+    }
+}
+
+impl From<&GroupError> for SorobanError {
+    fn from(err: &GroupError) -> SorobanError {
+        SorobanError::from_contract_error(*err as u32)
+    }
+}
+
+impl From<SorobanError> for GroupError {
+    fn from(_err: SorobanError) -> GroupError {
+        GroupError::HostError
+    }
+}
@@ -0,0 +1,89 @@
+use soroban_sdk::{vec, Address, Env, Vec};
+
+use crate::error::GroupError;
+use crate::storage::days_spanned;
+use crate::storage_types::{DataKey, GroupSession};
+
+/// Reserve the next group session ID and advance the counter
+pub fn next_session_id(env: &Env) -> u64 {
+    let session_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextGroupSessionId)
+        .unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextGroupSessionId, &(session_id + 1));
+    session_id
+}
+
+pub fn get_session(env: &Env, session_id: u64) -> Result<GroupSession, GroupError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::GroupSession(session_id))
+        .ok_or(GroupError::GroupSessionNotFound)
+}
+
+pub fn save_session(env: &Env, session: &GroupSession) {
+    env.storage().persistent().set(&DataKey::GroupSession(session.id), session);
+}
+
+/// Store a new session and register it in the doctor's schedule for every
+/// day it spans
+pub fn insert_session(env: &Env, session: &GroupSession) {
+    save_session(env, session);
+    for day in days_spanned(session.datetime, session.end_time()) {
+        push_index(env, DataKey::DoctorGroupSessions(session.doctor.clone(), day), session.id);
+    }
+}
+
+/// Take a session that no longer holds the doctor's time out of the schedule
+pub fn remove_from_schedule(env: &Env, session: &GroupSession) {
+    for day in days_spanned(session.datetime, session.end_time()) {
+        let key = DataKey::DoctorGroupSessions(session.doctor.clone(), day);
+        let mut ids = get_index(env, key.clone());
+        if let Some(index) = ids.first_index_of(session.id) {
+            ids.remove(index);
+            if ids.is_empty() {
+                env.storage().persistent().remove(&key);
+            } else {
+                env.storage().persistent().set(&key, &ids);
+            }
+        }
+    }
+}
+
+/// Scheduled group sessions of `doctor` that take up part of `day`
+pub fn get_doctor_day_sessions(env: &Env, doctor: &Address, day: u64) -> Vec<GroupSession> {
+    let mut sessions = vec![env];
+    for id in get_index(env, DataKey::DoctorGroupSessions(doctor.clone(), day)).iter() {
+        if let Ok(session) = get_session(env, id) {
+            sessions.push_back(session);
+        }
+    }
+    sessions
+}
+
+pub fn get_patient_session_ids(env: &Env, patient: &Address) -> Vec<u64> {
+    get_index(env, DataKey::PatientGroupSessions(patient.clone()))
+}
+
+/// Register `session_id` in `patient`'s index. Leaving a session keeps the
+/// entry, so the index also covers sessions the patient left.
+pub fn add_patient_session(env: &Env, patient: &Address, session_id: u64) {
+    let key = DataKey::PatientGroupSessions(patient.clone());
+    if !get_index(env, key.clone()).contains(session_id) {
+        push_index(env, key, session_id);
+    }
+}
+
+fn get_index(env: &Env, key: DataKey) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| vec![env])
+}
+
+fn push_index(env: &Env, key: DataKey, session_id: u64) {
+    let mut ids = get_index(env, key.clone());
+    ids.push_back(session_id);
+    env.storage().persistent().set(&key, &ids);
+}
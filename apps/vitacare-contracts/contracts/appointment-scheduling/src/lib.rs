@@ -1,11 +1,26 @@
 #![no_std]
 mod storage_types;
+mod storage;
+mod payments;
+mod credentials;
+mod waitlist;
+mod delegation;
+mod records;
+mod group;
+mod stats;
 mod error;
 mod contract;
 
 pub use contract::AppointmentSchedulingContract;
-pub use error::Error;
-pub use storage_types::{Appointment, AppointmentStatus};
+pub use error::{Error, GroupError};
+pub use storage_types::{
+    Appointment, AppointmentPage, AppointmentRating, AppointmentSeries, AppointmentStatus,
+    Attachment, AttachmentKind, AvailabilityWindow, Cancellation, CancellationInitiator, CancellationReason,
+    CancellationRecord,
+    ConsultationFee, DoctorReputation, DoctorStats, GroupSession, Handover, Modality, Payment, PaymentStatus, Priority,
+    RescheduleProposal, RescheduleRecord, SlotOffer, StatusChange, TelemedicineSession,
+    VisitDetails, WaitlistEntry,
+};
 
 #[cfg(test)]
 mod test;
\ No newline at end of file
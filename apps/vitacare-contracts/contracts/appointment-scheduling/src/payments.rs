@@ -0,0 +1,151 @@
+use soroban_sdk::{token, Address, Env};
+
+use crate::storage_types::{Appointment, ConsultationFee, DataKey, GroupSession, Payment, PaymentStatus};
+
+/// Denominator for `late_cancel_penalty_bps`.
+pub const BPS_DENOMINATOR: i128 = 10_000;
+
+/// Longest `late_cancel_window` a doctor may set.
+pub const MAX_LATE_CANCEL_WINDOW: u64 = 30 * 24 * 60 * 60;
+
+pub fn get_fee(env: &Env, doctor: &Address) -> Option<ConsultationFee> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ConsultationFee(doctor.clone()))
+}
+
+pub fn get_payment(env: &Env, appointment_id: u64) -> Option<Payment> {
+    env.storage().persistent().get(&DataKey::Payment(appointment_id))
+}
+
+/// Pull the doctor's current fee from whoever booked the appointment into the
+/// contract. The fee terms are copied onto the payment so later fee changes
+/// do not affect it.
+pub fn escrow_fee(env: &Env, appointment: &Appointment) {
+    escrow(env, DataKey::Payment(appointment.id), &appointment.doctor, &appointment.booked_by);
+}
+
+/// Pull the doctor's current fee from `patient` for a seat in `session`.
+pub fn escrow_seat_fee(env: &Env, session: &GroupSession, patient: &Address) {
+    escrow(env, DataKey::SeatPayment(session.id, patient.clone()), &session.doctor, patient);
+}
+
+pub fn get_seat_payment(env: &Env, session_id: u64, patient: &Address) -> Option<Payment> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SeatPayment(session_id, patient.clone()))
+}
+
+fn escrow(env: &Env, key: DataKey, doctor: &Address, payer: &Address) {
+    let Some(fee) = get_fee(env, doctor) else {
+        return;
+    };
+
+    token::Client::new(env, &fee.token).transfer(payer, &env.current_contract_address(), &fee.amount);
+
+    env.storage().persistent().set(&key, &Payment {
+        payer: payer.clone(),
+        token: fee.token,
+        amount: fee.amount,
+        status: PaymentStatus::Escrowed,
+        released_amount: 0,
+        refunded_amount: 0,
+        late_cancel_window: fee.late_cancel_window,
+        late_cancel_penalty_bps: fee.late_cancel_penalty_bps,
+    });
+}
+
+/// Pay `to_doctor` out of the escrow under `key` to `doctor` and return the
+/// rest to the payer.
+fn settle(env: &Env, key: DataKey, doctor: &Address, mut payment: Payment, to_doctor: i128) {
+    let client = token::Client::new(env, &payment.token);
+    let contract = env.current_contract_address();
+    let to_payer = payment.amount - to_doctor;
+
+    if to_doctor > 0 {
+        client.transfer(&contract, doctor, &to_doctor);
+    }
+    if to_payer > 0 {
+        client.transfer(&contract, &payment.payer, &to_payer);
+    }
+
+    payment.released_amount = to_doctor;
+    payment.refunded_amount = to_payer;
+    payment.status = if to_payer == 0 {
+        PaymentStatus::Released
+    } else {
+        PaymentStatus::Refunded
+    };
+    env.storage().persistent().set(&key, &payment);
+}
+
+fn escrowed(env: &Env, key: &DataKey) -> Option<Payment> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Payment>(key)
+        .filter(|payment| payment.status == PaymentStatus::Escrowed)
+}
+
+/// Part of `payment` the doctor keeps when the patient cancels at `now` a
+/// visit starting at `datetime`.
+fn late_cancel_penalty(payment: &Payment, datetime: u64, now: u64) -> i128 {
+    if datetime.saturating_sub(payment.late_cancel_window) <= now {
+        payment.amount * payment.late_cancel_penalty_bps as i128 / BPS_DENOMINATOR
+    } else {
+        0
+    }
+}
+
+/// Release the whole escrow to the doctor.
+pub fn release(env: &Env, appointment: &Appointment) {
+    let key = DataKey::Payment(appointment.id);
+    if let Some(payment) = escrowed(env, &key) {
+        let amount = payment.amount;
+        settle(env, key, &appointment.doctor, payment, amount);
+    }
+}
+
+/// Return the whole escrow to the payer.
+pub fn refund(env: &Env, appointment: &Appointment) {
+    let key = DataKey::Payment(appointment.id);
+    if let Some(payment) = escrowed(env, &key) {
+        settle(env, key, &appointment.doctor, payment, 0);
+    }
+}
+
+/// Refund a patient cancellation, keeping the late-cancellation penalty for
+/// the doctor when it happens within `late_cancel_window` of the start.
+pub fn refund_cancellation(env: &Env, appointment: &Appointment) {
+    let key = DataKey::Payment(appointment.id);
+    if let Some(payment) = escrowed(env, &key) {
+        let penalty = late_cancel_penalty(&payment, appointment.datetime, env.ledger().timestamp());
+        settle(env, key, &appointment.doctor, payment, penalty);
+    }
+}
+
+/// Release the seat fee `patient` paid for `session` to the doctor.
+pub fn release_seat(env: &Env, session: &GroupSession, patient: &Address) {
+    let key = DataKey::SeatPayment(session.id, patient.clone());
+    if let Some(payment) = escrowed(env, &key) {
+        let amount = payment.amount;
+        settle(env, key, &session.doctor, payment, amount);
+    }
+}
+
+/// Return the whole seat fee `patient` paid for `session`.
+pub fn refund_seat(env: &Env, session: &GroupSession, patient: &Address) {
+    let key = DataKey::SeatPayment(session.id, patient.clone());
+    if let Some(payment) = escrowed(env, &key) {
+        settle(env, key, &session.doctor, payment, 0);
+    }
+}
+
+/// Refund a seat `patient` gives up, keeping the late-cancellation penalty
+/// as for appointments.
+pub fn refund_seat_cancellation(env: &Env, session: &GroupSession, patient: &Address) {
+    let key = DataKey::SeatPayment(session.id, patient.clone());
+    if let Some(payment) = escrowed(env, &key) {
+        let penalty = late_cancel_penalty(&payment, session.datetime, env.ledger().timestamp());
+        settle(env, key, &session.doctor, payment, penalty);
+    }
+}
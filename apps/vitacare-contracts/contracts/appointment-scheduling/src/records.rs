@@ -0,0 +1,43 @@
+use soroban_sdk::{contractclient, Address, BytesN, Env, String};
+
+use crate::error::Error;
+use crate::storage_types::{Appointment, DataKey};
+
+/// The subset of the medical-record-management contract this contract calls.
+#[allow(dead_code)]
+#[contractclient(name = "MedicalRecordsClient")]
+pub trait MedicalRecordsInterface {
+    fn add_medical_record(
+        env: Env,
+        patient: Address,
+        doctor: Address,
+        record_hash: BytesN<32>,
+        description: String,
+    ) -> u32;
+}
+
+/// File the consultation summary of `appointment` with the configured
+/// medical records contract and return the new record's ID. The doctor must
+/// have been granted access by the patient there.
+pub fn add_record(
+    env: &Env,
+    appointment: &Appointment,
+    record_hash: &BytesN<32>,
+    description: &String,
+) -> Result<u32, Error> {
+    let records: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::MedicalRecordsContract)
+        .ok_or(Error::MedicalRecordsNotConfigured)?;
+
+    match MedicalRecordsClient::new(env, &records).try_add_medical_record(
+        &appointment.patient,
+        &appointment.doctor,
+        record_hash,
+        description,
+    ) {
+        Ok(Ok(record_id)) => Ok(record_id),
+        _ => Err(Error::MedicalRecordRejected),
+    }
+}
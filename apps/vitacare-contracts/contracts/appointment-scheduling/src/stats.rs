@@ -0,0 +1,55 @@
+use soroban_sdk::{Address, Env};
+
+use crate::storage_types::{CancellationInitiator, DataKey, DoctorStats};
+
+pub fn get_stats(env: &Env, doctor: &Address) -> DoctorStats {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DoctorStats(doctor.clone()))
+        .unwrap_or_default()
+}
+
+fn update(env: &Env, doctor: &Address, apply: impl FnOnce(&mut DoctorStats)) {
+    let mut stats = get_stats(env, doctor);
+    apply(&mut stats);
+    env.storage().persistent().set(&DataKey::DoctorStats(doctor.clone()), &stats);
+}
+
+/// Count a new booking made `lead_time` seconds ahead of the visit.
+pub fn record_booking(env: &Env, doctor: &Address, lead_time: u64) {
+    update(env, doctor, |stats| {
+        stats.booked += 1;
+        stats.total_lead_time += lead_time;
+        stats.average_lead_time = stats.total_lead_time / stats.booked as u64;
+    });
+}
+
+/// Move a booking made `lead_time` seconds ahead of the visit from `from` to
+/// `to`, as when a handover is accepted.
+pub fn move_booking(env: &Env, from: &Address, to: &Address, lead_time: u64) {
+    update(env, from, |stats| {
+        stats.booked = stats.booked.saturating_sub(1);
+        stats.total_lead_time = stats.total_lead_time.saturating_sub(lead_time);
+        stats.average_lead_time = match stats.booked {
+            0 => 0,
+            booked => stats.total_lead_time / booked as u64,
+        };
+    });
+    record_booking(env, to, lead_time);
+}
+
+pub fn record_completion(env: &Env, doctor: &Address) {
+    update(env, doctor, |stats| stats.completed += 1);
+}
+
+pub fn record_no_show(env: &Env, doctor: &Address) {
+    update(env, doctor, |stats| stats.no_shows += 1);
+}
+
+/// Count a cancellation on the side that initiated it.
+pub fn record_cancellation(env: &Env, doctor: &Address, initiator: CancellationInitiator) {
+    update(env, doctor, |stats| match initiator {
+        CancellationInitiator::Patient => stats.cancelled_by_patient += 1,
+        _ => stats.cancelled_by_provider += 1,
+    });
+}
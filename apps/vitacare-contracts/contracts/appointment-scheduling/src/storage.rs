@@ -0,0 +1,202 @@
+use soroban_sdk::{vec, Address, Env, Vec};
+
+use crate::error::Error;
+use crate::storage_types::{Appointment, AppointmentStatus, DataKey};
+
+/// Length of the calendar day (UTC) doctors' schedules are indexed by.
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Reserve the next appointment ID and advance the counter
+pub fn next_appointment_id(env: &Env) -> Result<u64, Error> {
+    let next_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextAppointmentId)
+        .ok_or(Error::NotInitialized)?;
+    env.storage().instance().set(&DataKey::NextAppointmentId, &(next_id + 1));
+    Ok(next_id)
+}
+
+pub fn get_appointment(env: &Env, appointment_id: u64) -> Result<Appointment, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Appointment(appointment_id))
+        .ok_or(Error::AppointmentNotFound)
+}
+
+pub fn save_appointment(env: &Env, appointment: &Appointment) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Appointment(appointment.id), appointment);
+}
+
+/// Store a new appointment and register it in the patient and doctor indexes
+/// and in the doctor's schedule
+pub fn insert_appointment(env: &Env, appointment: &Appointment) {
+    save_appointment(env, appointment);
+    push_index(env, DataKey::PatientAppointments(appointment.patient.clone()), appointment.id);
+    push_index(env, DataKey::DoctorAppointments(appointment.doctor.clone()), appointment.id);
+    add_to_schedule(env, appointment);
+    count_booking(env, &appointment.doctor, appointment.datetime);
+    if appointment.status == AppointmentStatus::Scheduled {
+        set_open_count(env, &appointment.patient, get_open_count(env, &appointment.patient) + 1);
+    }
+}
+
+/// Move `appointment` to `status`, keeping its patient's count of
+/// `Scheduled` appointments in step
+pub fn set_status(env: &Env, appointment: &mut Appointment, status: AppointmentStatus, changed_at: u64) {
+    if appointment.status == AppointmentStatus::Scheduled && status != AppointmentStatus::Scheduled {
+        let open = get_open_count(env, &appointment.patient);
+        set_open_count(env, &appointment.patient, open.saturating_sub(1));
+    }
+    appointment.set_status(status, changed_at);
+}
+
+/// Number of `patient`'s appointments still in `Scheduled` status
+pub fn get_open_count(env: &Env, patient: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PatientOpenCount(patient.clone()))
+        .unwrap_or(0)
+}
+
+fn set_open_count(env: &Env, patient: &Address, count: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::PatientOpenCount(patient.clone()), &count);
+}
+
+/// Register `appointment` under its doctor for every day it spans. The
+/// schedule only holds appointments that still take up the doctor's time.
+pub fn add_to_schedule(env: &Env, appointment: &Appointment) {
+    for day in days_spanned(appointment.datetime, appointment.end_time()) {
+        push_index(env, DataKey::DoctorSchedule(appointment.doctor.clone(), day), appointment.id);
+    }
+}
+
+/// Take `appointment` out of its doctor's schedule, once it is no longer
+/// active or before it moves
+pub fn remove_from_schedule(env: &Env, appointment: &Appointment) {
+    for day in days_spanned(appointment.datetime, appointment.end_time()) {
+        remove_from_index(env, DataKey::DoctorSchedule(appointment.doctor.clone(), day), appointment.id);
+    }
+}
+
+/// Active appointments of `doctor` that take up part of `day`, counted in
+/// days since the epoch. Includes those that started the day before.
+pub fn get_doctor_day_appointments(env: &Env, doctor: &Address, day: u64) -> Vec<Appointment> {
+    let mut appointments = vec![env];
+    for id in get_index(env, DataKey::DoctorSchedule(doctor.clone(), day)).iter() {
+        if let Ok(appointment) = get_appointment(env, id) {
+            appointments.push_back(appointment);
+        }
+    }
+    appointments
+}
+
+pub fn get_patient_appointment_ids(env: &Env, patient: &Address) -> Vec<u64> {
+    get_index(env, DataKey::PatientAppointments(patient.clone()))
+}
+
+pub fn get_doctor_appointment_ids(env: &Env, doctor: &Address) -> Vec<u64> {
+    get_index(env, DataKey::DoctorAppointments(doctor.clone()))
+}
+
+/// Move `appointment` to `doctor`, updating both doctors' indexes, schedules
+/// and daily booking counts
+pub fn reassign_doctor(env: &Env, appointment: &mut Appointment, doctor: &Address) {
+    remove_from_index(env, DataKey::DoctorAppointments(appointment.doctor.clone()), appointment.id);
+    remove_from_schedule(env, appointment);
+    uncount_booking(env, &appointment.doctor, appointment.datetime);
+
+    appointment.doctor = doctor.clone();
+    save_appointment(env, appointment);
+    push_index(env, DataKey::DoctorAppointments(doctor.clone()), appointment.id);
+    add_to_schedule(env, appointment);
+    count_booking(env, doctor, appointment.datetime);
+}
+
+/// Move `appointment` to `datetime`, updating its doctor's schedule and daily
+/// booking counts
+pub fn move_appointment(env: &Env, appointment: &mut Appointment, datetime: u64) {
+    remove_from_schedule(env, appointment);
+    uncount_booking(env, &appointment.doctor, appointment.datetime);
+
+    appointment.datetime = datetime;
+    save_appointment(env, appointment);
+    add_to_schedule(env, appointment);
+    count_booking(env, &appointment.doctor, datetime);
+}
+
+/// Number of `doctor`'s bookings starting on `day`, counted in days since the
+/// epoch. Every booking counts until it is canceled, including finished ones.
+pub fn get_day_booking_count(env: &Env, doctor: &Address, day: u64) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DoctorDayBookings(doctor.clone(), day))
+        .unwrap_or(0)
+}
+
+/// Count a booking of `doctor` starting at `datetime` toward that day
+pub fn count_booking(env: &Env, doctor: &Address, datetime: u64) {
+    let day = datetime / SECONDS_PER_DAY;
+    set_day_booking_count(env, doctor, day, get_day_booking_count(env, doctor, day) + 1);
+}
+
+/// Stop counting a booking of `doctor` starting at `datetime`, once it is
+/// canceled or moves
+pub fn uncount_booking(env: &Env, doctor: &Address, datetime: u64) {
+    let day = datetime / SECONDS_PER_DAY;
+    set_day_booking_count(env, doctor, day, get_day_booking_count(env, doctor, day).saturating_sub(1));
+}
+
+fn set_day_booking_count(env: &Env, doctor: &Address, day: u64, count: u32) {
+    let key = DataKey::DoctorDayBookings(doctor.clone(), day);
+    if count == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &count);
+    }
+}
+
+/// IDs of every appointment `user` takes part in, as patient first and then as doctor
+pub fn get_user_appointment_ids(env: &Env, user: &Address) -> Vec<u64> {
+    let mut ids = get_patient_appointment_ids(env, user);
+    for id in get_doctor_appointment_ids(env, user).iter() {
+        if !ids.contains(id) {
+            ids.push_back(id);
+        }
+    }
+    ids
+}
+
+fn get_index(env: &Env, key: DataKey) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| vec![env])
+}
+
+fn push_index(env: &Env, key: DataKey, appointment_id: u64) {
+    let mut ids = get_index(env, key.clone());
+    ids.push_back(appointment_id);
+    env.storage().persistent().set(&key, &ids);
+}
+
+fn remove_from_index(env: &Env, key: DataKey, appointment_id: u64) {
+    let mut ids = get_index(env, key.clone());
+    if let Some(index) = ids.first_index_of(appointment_id) {
+        ids.remove(index);
+        if ids.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &ids);
+        }
+    }
+}
+
+/// Days since the epoch that the non-empty range `[from, to)` touches
+pub fn days_spanned(from: u64, to: u64) -> core::ops::RangeInclusive<u64> {
+    from / SECONDS_PER_DAY..=(to - 1) / SECONDS_PER_DAY
+}
@@ -1,9 +1,45 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, BytesN, Vec};
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
-    Appointments,
+    Admin,
+    DoctorCredentialsContract,
+    MedicalRecordsContract,
     NextAppointmentId,
+    Appointment(u64),
+    PatientAppointments(Address),
+    PatientOpenCount(Address),
+    DoctorAppointments(Address),
+    DoctorSchedule(Address, u64),
+    DoctorDayBookings(Address, u64),
+    NextAvailabilityId,
+    Availability(Address),
+    PendingReschedule(u64),
+    ConsultationFee(Address),
+    Payment(u64),
+    SeatPayment(u64, Address),
+    NextOfferId,
+    Waitlist(Address),
+    SlotOffer(u64),
+    DoctorOffers(Address, u64),
+    NextSeriesId,
+    Series(u64),
+    Guardian(Address),
+    Delegates(Address),
+    Rating(u64),
+    DoctorReputation(Address),
+    Session(u64),
+    DailyCapacity(Address),
+    PatientAppointmentLimit,
+    ClinicStaff(Address),
+    NextGroupSessionId,
+    GroupSession(u64),
+    DoctorGroupSessions(Address, u64),
+    PatientGroupSessions(Address),
+    NextHandoverId,
+    Handover(u64),
+    Attachments(u64),
+    DoctorStats(Address),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -12,6 +48,101 @@ pub enum AppointmentStatus {
     Scheduled,
     Canceled,
     Completed,
+    CheckedIn,
+    NoShow,
+    Expired,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum CancellationInitiator {
+    Patient,
+    Doctor,
+    Institution,
+    ClinicStaff,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum CancellationReason {
+    PatientRequest,
+    DoctorUnavailable,
+    DoctorIllness,
+    FacilityClosure,
+    Emergency,
+    Other,
+    /// Gave way to an urgent booking.
+    Displaced,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum AttachmentKind {
+    IntakeQuestionnaire,
+    ReferralLetter,
+    Other,
+}
+
+/// Hash of a document the patient attached to an appointment before the visit.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    pub document_hash: BytesN<32>,
+    pub attached_at: u64,
+}
+
+/// Triage level of a visit, from lowest to highest.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[contracttype]
+pub enum Priority {
+    Routine,
+    Urgent,
+    Emergency,
+}
+
+/// Who cancelled an appointment, on which side, and why.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CancellationRecord {
+    pub cancelled_by: Address,
+    pub initiator: CancellationInitiator,
+    pub reason: CancellationReason,
+    pub cancelled_at: u64,
+}
+
+/// Whether an appointment was cancelled, and the record if so. Stands in for
+/// `Option<CancellationRecord>`, which a `#[contracttype]` struct cannot hold.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cancellation {
+    NotCancelled,
+    Cancelled(CancellationRecord),
+}
+
+/// A status the appointment entered and the ledger time it entered it.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusChange {
+    pub status: AppointmentStatus,
+    pub changed_at: u64,
+}
+
+/// How a consultation takes place. In-person visits reference a hospital `id`
+/// from hospital-data-management, telemedicine visits carry the hash of the
+/// meeting link shared off-chain.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Modality {
+    InPerson(u32),
+    Telemedicine(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VisitDetails {
+    pub modality: Modality,
+    pub duration: u64,
 }
 
 #[contracttype]
@@ -21,5 +152,248 @@ pub struct Appointment {
     pub patient: Address,
     pub doctor: Address,
     pub datetime: u64,
+    pub details: VisitDetails,
+    pub series_id: Option<u64>,
+    /// The patient, or the delegate or clinic staff member who booked for them.
+    pub booked_by: Address,
+    pub priority: Priority,
     pub status: AppointmentStatus,
-}
\ No newline at end of file
+    pub status_history: Vec<StatusChange>,
+    pub reschedule_history: Vec<RescheduleRecord>,
+    /// ID of the record filed with the medical records contract on completion.
+    pub medical_record_id: Option<u32>,
+    pub cancellation: Cancellation,
+}
+
+/// A new time suggested by one party, waiting for the other party's answer.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RescheduleProposal {
+    pub proposed_by: Address,
+    pub new_datetime: u64,
+    pub proposed_at: u64,
+}
+
+/// An accepted move of an appointment from one time to another.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RescheduleRecord {
+    pub previous_datetime: u64,
+    pub new_datetime: u64,
+    pub proposed_by: Address,
+    pub approved_by: Address,
+    pub rescheduled_at: u64,
+}
+
+/// A block of time a doctor publishes as bookable, split into fixed-length slots
+/// starting at `start`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AvailabilityWindow {
+    pub id: u64,
+    pub start: u64,
+    pub end: u64,
+    pub slot_duration: u64,
+    /// Only clinic staff booking urgent or emergency visits may use it.
+    pub urgent_only: bool,
+}
+
+/// What a doctor charges per appointment, in a Stellar Asset Contract token.
+/// Patients cancelling within `late_cancel_window` seconds of the start forfeit
+/// `late_cancel_penalty_bps` of the fee to the doctor.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsultationFee {
+    pub token: Address,
+    pub amount: i128,
+    pub late_cancel_window: u64,
+    pub late_cancel_penalty_bps: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum PaymentStatus {
+    Escrowed,
+    /// Paid out to the doctor in full.
+    Released,
+    /// Returned to the patient, in full or minus a late-cancellation penalty.
+    Refunded,
+}
+
+/// Fee held by the contract for one appointment, with the terms it was booked under.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payment {
+    /// Whoever booked the appointment. Refunds go back to them.
+    pub payer: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub status: PaymentStatus,
+    pub released_amount: i128,
+    pub refunded_amount: i128,
+    pub late_cancel_window: u64,
+    pub late_cancel_penalty_bps: u32,
+}
+
+/// Appointments booked together at a fixed `interval`, in chronological order.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppointmentSeries {
+    pub id: u64,
+    pub patient: Address,
+    pub doctor: Address,
+    pub interval: u64,
+    pub appointment_ids: Vec<u64>,
+    pub created_at: u64,
+}
+
+/// Attendance of a telemedicine appointment. Each join time is set by the
+/// participant's own signed call.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct TelemedicineSession {
+    pub doctor_joined_at: Option<u64>,
+    pub patient_joined_at: Option<u64>,
+    pub ended_at: Option<u64>,
+}
+
+/// A patient's rating of a completed appointment.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppointmentRating {
+    pub appointment_id: u64,
+    pub doctor: Address,
+    /// From 1 to 5.
+    pub score: u32,
+    pub review_hash: BytesN<32>,
+    pub rated_at: u64,
+}
+
+/// Aggregate of every rating a doctor has received.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct DoctorReputation {
+    pub rating_count: u32,
+    pub score_sum: u64,
+    /// Running average score in hundredths, e.g. 450 for 4.5.
+    pub average: u32,
+}
+
+/// One doctor seeing up to `seats` patients at once, such as a prenatal class.
+/// Only `Scheduled` and `Completed` are used as its status.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupSession {
+    pub id: u64,
+    pub doctor: Address,
+    pub datetime: u64,
+    pub details: VisitDetails,
+    pub seats: u32,
+    pub attendees: Vec<Address>,
+    /// Attendees who checked in. After completion the others count as absent.
+    pub checked_in: Vec<Address>,
+    pub status: AppointmentStatus,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+}
+
+/// Future appointments of `from_doctor` passed to a colleague at the same
+/// institution. They move once `to_doctor` accepts.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Handover {
+    pub id: u64,
+    pub from_doctor: Address,
+    pub to_doctor: Address,
+    /// The original doctor or their institution.
+    pub proposed_by: Address,
+    pub appointment_ids: Vec<u64>,
+    pub proposed_at: u64,
+    pub accepted_at: Option<u64>,
+}
+
+/// Running counters of a doctor's appointments, kept up to date as they
+/// change status.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct DoctorStats {
+    pub booked: u32,
+    pub completed: u32,
+    pub cancelled_by_patient: u32,
+    /// Cancelled by the doctor, their institution or clinic staff.
+    pub cancelled_by_provider: u32,
+    pub no_shows: u32,
+    /// Sum over all bookings of the time between booking and the visit.
+    pub total_lead_time: u64,
+    pub average_lead_time: u64,
+}
+
+/// A patient waiting for any slot of a doctor starting between `earliest` and `latest`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WaitlistEntry {
+    pub patient: Address,
+    pub earliest: u64,
+    pub latest: u64,
+    pub joined_at: u64,
+}
+
+/// A freed slot reserved for one waitlisted patient until `expires_at`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotOffer {
+    pub id: u64,
+    pub doctor: Address,
+    pub patient: Address,
+    pub datetime: u64,
+    pub details: VisitDetails,
+    pub expires_at: u64,
+}
+
+/// One page of a user's appointments. `next_cursor` is `None` on the last page.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppointmentPage {
+    pub appointments: Vec<Appointment>,
+    pub next_cursor: Option<u32>,
+}
+
+impl GroupSession {
+    pub fn end_time(&self) -> u64 {
+        self.datetime + self.details.duration
+    }
+}
+
+impl Appointment {
+    pub fn end_time(&self) -> u64 {
+        self.datetime + self.details.duration
+    }
+
+    /// Scheduled and checked-in appointments still hold the doctor's time.
+    pub fn is_active(&self) -> bool {
+        matches!(self.status, AppointmentStatus::Scheduled | AppointmentStatus::CheckedIn)
+    }
+
+    /// Time between booking and the start originally booked, as counted in
+    /// the doctor's stats.
+    pub fn booking_lead_time(&self) -> u64 {
+        let booked_at = self.status_history.first().map_or(0, |change| change.changed_at);
+        let booked_for = self
+            .reschedule_history
+            .first()
+            .map_or(self.datetime, |record| record.previous_datetime);
+        booked_for.saturating_sub(booked_at)
+    }
+
+    pub fn cancellation_record(&self) -> Option<CancellationRecord> {
+        match &self.cancellation {
+            Cancellation::Cancelled(record) => Some(record.clone()),
+            Cancellation::NotCancelled => None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: AppointmentStatus, changed_at: u64) {
+        self.status = status;
+        self.status_history.push_back(StatusChange { status, changed_at });
+    }
+}
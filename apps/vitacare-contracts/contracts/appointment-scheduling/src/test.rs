@@ -1,16 +1,21 @@
 #![cfg(test)]
 use super::*;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger, LedgerInfo},
-    Address, Env, Map, 
+    testutils::{Address as _, Ledger, LedgerInfo, MockAuth, MockAuthInvoke},
+    token, vec, Address, Bytes, BytesN, Env, IntoVal, String,
 };
-use crate::storage_types::{Appointment, AppointmentStatus, DataKey};
-fn create_env<'a>() -> Env {
+use doctor_credentials::{DoctorCredentials, DoctorCredentialsClient};
+use medical_record_management::{MedicalRecordsContract, MedicalRecordsContractClient};
+use crate::storage_types::{
+    AppointmentStatus, CancellationInitiator, CancellationReason, DataKey, Modality, PaymentStatus,
+    AttachmentKind, Priority, VisitDetails,
+};
+fn create_env() -> Env {
     Env::default()
 }
 
 fn register_contract(env: &Env) -> Address {
-    env.register_contract(None, AppointmentSchedulingContract)
+    env.register(AppointmentSchedulingContract, ())
 }
 
 fn setup_ledger_time(env: &Env, timestamp: u64) {
@@ -30,12 +35,75 @@ fn create_client<'a>(env: &'a Env, contract_id: &'a Address) -> contract::Appoin
     contract::AppointmentSchedulingContractClient::new(env, contract_id)
 }
 
+const SLOT: u64 = 1800;
+const FEE: i128 = 1_000;
+
+// Registers a Stellar asset and mints `FEE` for each patient
+fn create_token<'a>(env: &'a Env, patients: &[&Address]) -> token::Client<'a> {
+    let admin = Address::generate(env);
+    let asset = env.register_stellar_asset_contract_v2(admin);
+    let asset_admin = token::StellarAssetClient::new(env, &asset.address());
+    for patient in patients {
+        asset_admin.mint(patient, &FEE);
+    }
+    token::Client::new(env, &asset.address())
+}
+
+fn in_person() -> VisitDetails {
+    VisitDetails {
+        modality: Modality::InPerson(1),
+        duration: SLOT,
+    }
+}
+
+fn telemedicine(env: &Env, duration: u64) -> VisitDetails {
+    VisitDetails {
+        modality: Modality::Telemedicine(BytesN::from_array(env, &[7u8; 32])),
+        duration,
+    }
+}
+
+// Deploys doctor-credentials, wires it into `client` and registers `doctor`,
+// verified by `institution` when one is given
+fn setup_credentials<'a>(
+    env: &'a Env,
+    client: &contract::AppointmentSchedulingContractClient,
+    doctor: &Address,
+    institution: Option<&Address>,
+) -> DoctorCredentialsClient<'a> {
+    let credentials = DoctorCredentialsClient::new(env, &env.register(DoctorCredentials, ()));
+    client.set_doctor_credentials_contract(&credentials.address);
+
+    credentials.register_doctor(
+        doctor,
+        &String::from_str(env, "Dr. Jane Doe"),
+        &String::from_str(env, "Cardiology"),
+        &Bytes::from_array(env, &[1u8; 32]),
+    );
+    if let Some(institution) = institution {
+        credentials.add_institution(institution);
+        credentials.verify_doctor(doctor, institution);
+    }
+    credentials
+}
+
+// Publishes an eight-slot window for `doctor` starting at `start`
+fn publish_availability(
+    client: &contract::AppointmentSchedulingContractClient,
+    doctor: &Address,
+    start: u64,
+) -> u64 {
+    client.add_availability(doctor, &start, &(start + 8 * SLOT), &SLOT)
+}
+
 #[test]
 fn test_initialize_success() {
     let env = create_env();
+    env.mock_all_auths();
     let contract_id = register_contract(&env);
     let client = create_client(&env, &contract_id);
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     env.as_contract(&contract_id, || {
         // verify next ID exists and is 1
@@ -44,14 +112,21 @@ fn test_initialize_success() {
             .expect("Option should be Some");
         assert_eq!(next_id, 1);
 
-         // verify appointments map exists and is empty
-        let appointments: Map<u64, Appointment> = env.storage().persistent()
-            .get::<DataKey, Map<u64, Appointment>>(&DataKey::Appointments) 
-            .expect("Option should be Some");
-        assert_eq!(appointments.len(), 0);
+        // verify no appointment has been stored yet
+        assert!(!env.storage().persistent().has(&DataKey::Appointment(1)));
     });
 }
 
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_initialize_requires_admin_signature() {
+    let env = create_env();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+}
+
 #[test]
 fn test_create_appointment_success() {
     let env = create_env();
@@ -59,15 +134,17 @@ fn test_create_appointment_success() {
     let contract_id = register_contract(&env);
     let client = create_client(&env, &contract_id);
 
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let patient = Address::generate(&env);
     let doctor = Address::generate(&env);
     let appointment_time: u64 = 1746000000;
     setup_ledger_time(&env, appointment_time - 1000);
+    publish_availability(&client, &doctor, appointment_time);
 
     // Call create_appointment directly - should not panic and return ID 1
-    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time);
+    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time, &in_person());
     assert_eq!(appointment_id, 1);
 
     // Verify appointment exists and has correct status using get_appointment_details
@@ -86,15 +163,17 @@ fn test_cancel_appointment_success() {
     let contract_id = register_contract(&env);
     let client = create_client(&env, &contract_id);
 
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let patient = Address::generate(&env);
     let doctor = Address::generate(&env);
     let appointment_time: u64 = 1746000000;
     setup_ledger_time(&env, appointment_time - 1000);
+    publish_availability(&client, &doctor, appointment_time);
 
     // Setup: Create an appointment
-    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time);
+    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time, &in_person());
     assert_eq!(appointment_id, 1);
 
     // Test: Cancel the appointment directly - should not panic
@@ -103,6 +182,14 @@ fn test_cancel_appointment_success() {
     // Verify: Check status is Canceled
     let appointment = client.get_appointment_details(&appointment_id);
     assert_eq!(appointment.status, AppointmentStatus::Canceled);
+
+    let cancellation = client.get_cancellation(&appointment_id).unwrap();
+    assert_eq!(cancellation.cancelled_by, patient);
+    assert_eq!(cancellation.initiator, CancellationInitiator::Patient);
+    assert_eq!(cancellation.reason, CancellationReason::PatientRequest);
+
+    // The record is part of the appointment itself
+    assert_eq!(appointment.cancellation, Cancellation::Cancelled(cancellation));
 }
 
 #[test]
@@ -112,15 +199,17 @@ fn test_complete_appointment_success() {
     let contract_id = register_contract(&env);
     let client = create_client(&env, &contract_id);
 
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let patient = Address::generate(&env);
     let doctor = Address::generate(&env);
     let appointment_time: u64 = 1746000000;
     setup_ledger_time(&env, appointment_time - 1000);
+    publish_availability(&client, &doctor, appointment_time);
 
     // Setup: Create an appointment
-    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time);
+    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time, &in_person());
     assert_eq!(appointment_id, 1);
 
     // Test: Complete the appointment directly - should not panic
@@ -138,15 +227,17 @@ fn test_get_appointment_details_success() {
     let contract_id = register_contract(&env);
     let client = create_client(&env, &contract_id);
 
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let patient = Address::generate(&env);
     let doctor = Address::generate(&env);
     let appointment_time: u64 = 1746000000;
     setup_ledger_time(&env, appointment_time - 1000);
+    publish_availability(&client, &doctor, appointment_time);
 
     // Setup: Create an appointment
-    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time);
+    let appointment_id = client.create_appointment(&patient, &doctor, &appointment_time, &in_person());
     assert_eq!(appointment_id, 1);
 
     // Test: Get details directly - should not panic
@@ -165,18 +256,20 @@ fn test_get_appointments_success() {
     let contract_id = register_contract(&env);
     let client = create_client(&env, &contract_id);
 
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let patient1 = Address::generate(&env);
     let doctor1 = Address::generate(&env);
     let patient2 = Address::generate(&env);
     let time1: u64 = 1746000000;
-    let time2: u64 = 1746000100;
+    let time2: u64 = time1 + SLOT;
     setup_ledger_time(&env, time1 - 1000);
+    publish_availability(&client, &doctor1, time1);
 
     // Setup: Create two appointments
-    let id1 = client.create_appointment(&patient1, &doctor1, &time1);
-    let id2 = client.create_appointment(&patient2, &doctor1, &time2); // Same doctor
+    let id1 = client.create_appointment(&patient1, &doctor1, &time1, &in_person());
+    let id2 = client.create_appointment(&patient2, &doctor1, &time2, &in_person()); // Same doctor
 
     // Test: Get appointments for patient1 - should not panic
     let p1_appts = client.get_appointments(&patient1);
@@ -198,3 +291,2832 @@ fn test_get_appointments_success() {
     assert_eq!(other_appts.len(), 0);
 }
 
+#[test]
+fn test_add_availability_success() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+
+    let window_id = publish_availability(&client, &doctor, start);
+    assert_eq!(window_id, 1);
+
+    let windows = client.get_availability(&doctor);
+    assert_eq!(windows.len(), 1);
+    let window = windows.get(0).unwrap();
+    assert_eq!(window.start, start);
+    assert_eq!(window.end, start + 8 * SLOT);
+    assert_eq!(window.slot_duration, SLOT);
+
+    let slots = client.get_available_slots(&doctor, &window_id);
+    assert_eq!(slots.len(), 8);
+    assert_eq!(slots.get(7).unwrap(), start + 7 * SLOT);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_add_overlapping_availability_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &doctor, start + 2 * SLOT);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_create_appointment_outside_availability_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    // Not aligned to a slot boundary
+    client.create_appointment(&patient, &doctor, &(start + 100), &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_double_booking_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient1 = Address::generate(&env);
+    let patient2 = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_appointment(&patient1, &doctor, &start, &in_person());
+    client.create_appointment(&patient2, &doctor, &start, &in_person());
+}
+
+#[test]
+fn test_canceled_slot_can_be_rebooked() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient1 = Address::generate(&env);
+    let patient2 = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    let window_id = publish_availability(&client, &doctor, start);
+
+    let id1 = client.create_appointment(&patient1, &doctor, &start, &in_person());
+    assert_eq!(client.get_available_slots(&doctor, &window_id).len(), 7);
+
+    client.cancel_appointment(&id1);
+    assert_eq!(client.get_available_slots(&doctor, &window_id).len(), 8);
+
+    let id2 = client.create_appointment(&patient2, &doctor, &start, &in_person());
+    assert_eq!(client.get_appointment_details(&id2).patient, patient2);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_removed_availability_cannot_be_booked() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    let window_id = publish_availability(&client, &doctor, start);
+
+    client.remove_availability(&doctor, &window_id);
+    assert_eq!(client.get_availability(&doctor).len(), 0);
+
+    client.create_appointment(&patient, &doctor, &start, &in_person());
+}
+
+#[test]
+fn test_reschedule_accepted_by_other_party() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    // Patient proposes, appointment stays put until the doctor answers
+    client.propose_reschedule(&appointment_id, &patient, &(start + 2 * SLOT));
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.datetime, start);
+    let proposal = client.get_pending_reschedule(&appointment_id).unwrap();
+    assert_eq!(proposal.proposed_by, patient);
+    assert_eq!(proposal.new_datetime, start + 2 * SLOT);
+
+    client.accept_reschedule(&appointment_id, &doctor);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.id, appointment_id);
+    assert_eq!(appointment.datetime, start + 2 * SLOT);
+    assert_eq!(client.get_pending_reschedule(&appointment_id), None);
+    assert_eq!(appointment.reschedule_history.len(), 1);
+    let record = appointment.reschedule_history.get(0).unwrap();
+    assert_eq!(record.previous_datetime, start);
+    assert_eq!(record.new_datetime, start + 2 * SLOT);
+    assert_eq!(record.proposed_by, patient);
+    assert_eq!(record.approved_by, doctor);
+
+    // The original slot is free again
+    let other_patient = Address::generate(&env);
+    client.create_appointment(&other_patient, &doctor, &start, &in_person());
+}
+
+#[test]
+fn test_reschedule_declined() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    client.propose_reschedule(&appointment_id, &doctor, &(start + SLOT));
+    client.decline_reschedule(&appointment_id, &patient);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.datetime, start);
+    assert_eq!(client.get_pending_reschedule(&appointment_id), None);
+    assert_eq!(appointment.reschedule_history.len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_proposer_cannot_accept_own_reschedule() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    client.propose_reschedule(&appointment_id, &patient, &(start + SLOT));
+    client.accept_reschedule(&appointment_id, &patient);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_reschedule_to_booked_slot_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient1 = Address::generate(&env);
+    let patient2 = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient1, &doctor, &start, &in_person());
+    client.create_appointment(&patient2, &doctor, &(start + SLOT), &in_person());
+
+    client.propose_reschedule(&appointment_id, &patient1, &(start + SLOT));
+}
+
+#[test]
+fn test_create_telemedicine_appointment_with_duration() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    let window_id = publish_availability(&client, &doctor, start);
+
+    let details = telemedicine(&env, 2 * SLOT);
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &details);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.details, details);
+    assert_eq!(appointment.details.modality, Modality::Telemedicine(BytesN::from_array(&env, &[7u8; 32])));
+    assert_eq!(appointment.end_time(), start + 2 * SLOT);
+
+    // A one-hour visit takes two half-hour slots
+    let slots = client.get_available_slots(&doctor, &window_id);
+    assert_eq!(slots.len(), 6);
+    assert_eq!(slots.get(0).unwrap(), start + 2 * SLOT);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_booking_inside_longer_appointment_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient1 = Address::generate(&env);
+    let patient2 = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_appointment(&patient1, &doctor, &start, &telemedicine(&env, 2 * SLOT));
+    client.create_appointment(&patient2, &doctor, &(start + SLOT), &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")]
+fn test_visit_longer_than_allowed_slots_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    // A single booking may not take the whole eight-slot window
+    client.create_appointment(&patient, &doctor, &start, &telemedicine(&env, 8 * SLOT));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")]
+fn test_visit_not_spanning_whole_slots_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_appointment(&patient, &doctor, &start, &telemedicine(&env, SLOT + 60));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")]
+fn test_telemedicine_appointment_without_meeting_link_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let details = VisitDetails {
+        modality: Modality::Telemedicine(BytesN::from_array(&env, &[0u8; 32])),
+        duration: SLOT,
+    };
+    client.create_appointment(&patient, &doctor, &start, &details);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_appointment_running_past_window_end_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    // Last slot of the window cannot hold a one-hour visit
+    client.create_appointment(&patient, &doctor, &(start + 7 * SLOT), &telemedicine(&env, 2 * SLOT));
+}
+
+#[test]
+fn test_get_appointments_page() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    for i in 0..5 {
+        client.create_appointment(&patient, &doctor, &(start + i * SLOT), &in_person());
+    }
+
+    let page = client.get_appointments_page(&patient, &0, &2);
+    assert_eq!(page.appointments.len(), 2);
+    assert_eq!(page.appointments.get(0).unwrap().id, 1);
+    assert_eq!(page.next_cursor, Some(2));
+
+    let page = client.get_appointments_page(&patient, &2, &2);
+    assert_eq!(page.appointments.get(0).unwrap().id, 3);
+    assert_eq!(page.next_cursor, Some(4));
+
+    let page = client.get_appointments_page(&patient, &4, &2);
+    assert_eq!(page.appointments.len(), 1);
+    assert_eq!(page.appointments.get(0).unwrap().id, 5);
+    assert_eq!(page.next_cursor, None);
+
+    // Cursor past the end yields an empty last page
+    let page = client.get_appointments_page(&doctor, &10, &2);
+    assert_eq!(page.appointments.len(), 0);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #14)")]
+fn test_get_appointments_page_zero_limit_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    client.get_appointments_page(&patient, &0, &0);
+}
+
+#[test]
+fn test_get_appointments_between() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let id1 = client.create_appointment(&patient, &doctor, &start, &in_person());
+    let id2 = client.create_appointment(&patient, &doctor, &(start + 2 * SLOT), &in_person());
+    client.create_appointment(&patient, &doctor, &(start + 4 * SLOT), &in_person());
+
+    let appointments = client.get_appointments_between(&patient, &start, &(start + 4 * SLOT));
+    assert_eq!(appointments.len(), 2);
+    assert_eq!(appointments.get(0).unwrap().id, id1);
+    assert_eq!(appointments.get(1).unwrap().id, id2);
+
+    let doctor_appointments = client.get_appointments_between(&doctor, &(start + SLOT), &(start + 8 * SLOT));
+    assert_eq!(doctor_appointments.len(), 2);
+}
+
+#[test]
+fn test_booking_reads_stay_bounded_as_history_grows() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    let day: u64 = 24 * 60 * 60;
+    setup_ledger_time(&env, start - 1000);
+    for d in 0..6 {
+        publish_availability(&client, &doctor, start + d * day);
+    }
+    client.set_daily_capacity(&doctor, &8);
+    client.set_patient_appointment_limit(&48);
+
+    // 48 bookings, eight a day. Each booking only reads that day's schedule
+    // and the patient's open count, so the first booking of a day costs the
+    // same however many came before.
+    let mut first_booking_reads = 0;
+    for d in 0..6 {
+        for i in 0..8 {
+            client.create_appointment(&patient, &doctor, &(start + d * day + i * SLOT), &in_person());
+            let read_entries = env.cost_estimate().resources().read_entries;
+            if d == 0 && i == 0 {
+                first_booking_reads = read_entries;
+            }
+            if i == 0 {
+                assert_eq!(read_entries, first_booking_reads);
+            }
+            assert!(read_entries <= first_booking_reads + i as u32);
+        }
+    }
+    assert_eq!(client.get_appointments(&doctor).len(), 48);
+    assert!(first_booking_reads + 8 < 40);
+}
+
+#[test]
+fn test_check_in_then_complete() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 3600);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    setup_ledger_time(&env, start - 600);
+    client.check_in(&appointment_id);
+    assert_eq!(client.get_appointment_details(&appointment_id).status, AppointmentStatus::CheckedIn);
+
+    setup_ledger_time(&env, start + SLOT);
+    client.complete_appointment(&appointment_id);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.status, AppointmentStatus::Completed);
+    let history = appointment.status_history;
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0).unwrap().status, AppointmentStatus::Scheduled);
+    assert_eq!(history.get(0).unwrap().changed_at, start - 3600);
+    assert_eq!(history.get(1).unwrap().status, AppointmentStatus::CheckedIn);
+    assert_eq!(history.get(1).unwrap().changed_at, start - 600);
+    assert_eq!(history.get(2).unwrap().status, AppointmentStatus::Completed);
+    assert_eq!(history.get(2).unwrap().changed_at, start + SLOT);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #16)")]
+fn test_check_in_too_early_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 3600);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.check_in(&appointment_id);
+}
+
+#[test]
+fn test_mark_no_show_after_grace_period() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    setup_ledger_time(&env, start + 20 * 60);
+    client.mark_no_show(&appointment_id);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.status, AppointmentStatus::NoShow);
+    assert_eq!(appointment.status_history.get(1).unwrap().changed_at, start + 20 * 60);
+
+    // The slot is no longer held
+    let slots = client.get_available_slots(&doctor, &1);
+    assert!(!slots.contains(start));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #17)")]
+fn test_mark_no_show_during_grace_period_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    setup_ledger_time(&env, start + 60);
+    client.mark_no_show(&appointment_id);
+}
+
+#[test]
+fn test_expire_stale_appointment() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    setup_ledger_time(&env, start + SLOT + 24 * 60 * 60);
+    client.expire_appointment(&appointment_id);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.status, AppointmentStatus::Expired);
+    assert_eq!(appointment.status_history.get(1).unwrap().changed_at, start + SLOT + 24 * 60 * 60);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #17)")]
+fn test_expire_recent_appointment_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    setup_ledger_time(&env, start + SLOT);
+    client.expire_appointment(&appointment_id);
+}
+
+#[test]
+fn test_fee_escrowed_and_released_on_completion() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[&patient]);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(24 * 60 * 60), &5_000);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    assert_eq!(token.balance(&patient), 0);
+    assert_eq!(token.balance(&contract_id), FEE);
+
+    let payment = client.get_payment(&appointment_id).unwrap();
+    assert_eq!(payment.amount, FEE);
+    assert_eq!(payment.status, PaymentStatus::Escrowed);
+
+    client.complete_appointment(&appointment_id);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(token.balance(&doctor), FEE);
+    assert_eq!(client.get_payment(&appointment_id).unwrap().status, PaymentStatus::Released);
+}
+
+#[test]
+fn test_early_cancellation_refunds_in_full() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[&patient]);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 3 * 24 * 60 * 60);
+    publish_availability(&client, &doctor, start);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(24 * 60 * 60), &5_000);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.cancel_appointment(&appointment_id);
+
+    assert_eq!(token.balance(&patient), FEE);
+    assert_eq!(token.balance(&doctor), 0);
+    let payment = client.get_payment(&appointment_id).unwrap();
+    assert_eq!(payment.status, PaymentStatus::Refunded);
+    assert_eq!(payment.refunded_amount, FEE);
+}
+
+#[test]
+fn test_late_cancellation_keeps_penalty() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[&patient]);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 3 * 24 * 60 * 60);
+    publish_availability(&client, &doctor, start);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(24 * 60 * 60), &2_500);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    // Raising the fee afterwards does not change this booking's terms
+    client.set_consultation_fee(&doctor, &token.address, &(2 * FEE), &(24 * 60 * 60), &10_000);
+
+    setup_ledger_time(&env, start - 60 * 60);
+    client.cancel_appointment(&appointment_id);
+
+    assert_eq!(token.balance(&patient), 750);
+    assert_eq!(token.balance(&doctor), 250);
+    let payment = client.get_payment(&appointment_id).unwrap();
+    assert_eq!(payment.released_amount, 250);
+    assert_eq!(payment.refunded_amount, 750);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")]
+fn test_set_consultation_fee_invalid_penalty_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[]);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &0, &10_001);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")]
+fn test_set_consultation_fee_window_too_long_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[]);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(31 * 24 * 60 * 60), &5_000);
+}
+
+#[test]
+fn test_cancel_within_longest_late_cancel_window() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[&patient]);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 3 * 24 * 60 * 60);
+    publish_availability(&client, &doctor, start);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(30 * 24 * 60 * 60), &5_000);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.cancel_appointment(&appointment_id);
+
+    assert_eq!(token.balance(&patient), FEE / 2);
+    assert_eq!(token.balance(&doctor), FEE / 2);
+}
+
+#[test]
+fn test_create_appointment_with_verified_doctor() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    setup_credentials(&env, &client, &doctor, Some(&Address::generate(&env)));
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    assert_eq!(client.get_appointment_details(&appointment_id).doctor, doctor);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #19)")]
+fn test_create_appointment_with_unverified_doctor_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    setup_credentials(&env, &client, &doctor, None);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_appointment(&patient, &doctor, &start, &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #19)")]
+fn test_create_appointment_with_unregistered_doctor_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let registered = Address::generate(&env);
+    setup_credentials(&env, &client, &registered, Some(&Address::generate(&env)));
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_appointment(&patient, &doctor, &start, &in_person());
+}
+
+#[test]
+fn test_join_and_leave_waitlist() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+
+    client.join_waitlist(&patient, &doctor, &start, &(start + 8 * SLOT));
+    let entries = client.get_waitlist(&doctor);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries.get(0).unwrap().patient, patient);
+
+    client.leave_waitlist(&patient, &doctor);
+    assert_eq!(client.get_waitlist(&doctor).len(), 0);
+}
+
+#[test]
+fn test_cancellation_offers_slot_to_waitlisted_patient() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let early_bird = Address::generate(&env);
+    let waiting = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 24 * 60 * 60);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id =
+        client.create_appointment(&patient, &doctor, &(start + 4 * SLOT), &telemedicine(&env, SLOT));
+
+    // Only the second patient's range covers the freed slot
+    client.join_waitlist(&early_bird, &doctor, &start, &(start + SLOT));
+    client.join_waitlist(&waiting, &doctor, &(start + 2 * SLOT), &(start + 8 * SLOT));
+
+    client.cancel_appointment(&appointment_id);
+
+    let offer = client.get_waitlist_offer(&1);
+    assert_eq!(offer.patient, waiting);
+    assert_eq!(offer.datetime, start + 4 * SLOT);
+    assert_eq!(client.get_waitlist(&doctor).len(), 1);
+
+    // The offered slot is held for the waitlisted patient
+    let window_slots = client.get_available_slots(&doctor, &1);
+    assert!(!window_slots.contains(start + 4 * SLOT));
+
+    // The claimer books with their own visit details, not the previous patient's
+    let new_id = client.claim_waitlist_offer(&offer.id, &in_person());
+    let appointment = client.get_appointment_details(&new_id);
+    assert_eq!(appointment.patient, waiting);
+    assert_eq!(appointment.datetime, start + 4 * SLOT);
+    assert_eq!(appointment.details, in_person());
+    assert_eq!(appointment.status, AppointmentStatus::Scheduled);
+}
+
+#[test]
+fn test_offers_on_other_days_do_not_add_booking_reads() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    let day: u64 = 24 * 60 * 60;
+    setup_ledger_time(&env, start - day);
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &doctor, start + day);
+
+    client.create_appointment(&patient, &doctor, &(start + day), &in_person());
+    let reads_before = env.cost_estimate().resources().read_entries;
+
+    // Four freed slots on the first day, each held by an offer
+    for i in 0..4 {
+        let waiting = Address::generate(&env);
+        client.join_waitlist(&waiting, &doctor, &start, &(start + 8 * SLOT));
+        let appointment_id =
+            client.create_appointment(&Address::generate(&env), &doctor, &(start + i * SLOT), &in_person());
+        client.cancel_appointment(&appointment_id);
+    }
+    assert_eq!(client.get_waitlist_offer(&4).datetime, start + 3 * SLOT);
+
+    // Booking on the next day only reads that day's offers. The one extra
+    // entry is the appointment booked there before.
+    client.create_appointment(&patient, &doctor, &(start + day + SLOT), &in_person());
+    assert_eq!(env.cost_estimate().resources().read_entries, reads_before + 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")]
+fn test_claim_offer_longer_than_freed_slot_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let waiting = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 24 * 60 * 60);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.join_waitlist(&waiting, &doctor, &start, &(start + 8 * SLOT));
+    client.cancel_appointment(&appointment_id);
+
+    client.claim_waitlist_offer(&1, &telemedicine(&env, 2 * SLOT));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_offered_slot_cannot_be_booked_by_others() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let waiting = Address::generate(&env);
+    let other = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 24 * 60 * 60);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.join_waitlist(&waiting, &doctor, &start, &(start + 8 * SLOT));
+    client.cancel_appointment(&appointment_id);
+
+    client.create_appointment(&other, &doctor, &start, &in_person());
+}
+
+#[test]
+fn test_expired_offer_passes_to_next_patient() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    let now = start - 24 * 60 * 60;
+    setup_ledger_time(&env, now);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.join_waitlist(&first, &doctor, &start, &(start + 8 * SLOT));
+    client.join_waitlist(&second, &doctor, &start, &(start + 8 * SLOT));
+    client.cancel_appointment(&appointment_id);
+    assert_eq!(client.get_waitlist_offer(&1).patient, first);
+
+    setup_ledger_time(&env, now + 3 * 60 * 60);
+    client.expire_waitlist_offer(&1);
+
+    let offer = client.get_waitlist_offer(&2);
+    assert_eq!(offer.patient, second);
+    assert_eq!(offer.datetime, start);
+    assert_eq!(client.get_waitlist(&doctor).len(), 0);
+
+    client.claim_waitlist_offer(&2, &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #24)")]
+fn test_expire_open_offer_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let waiting = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 24 * 60 * 60);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.join_waitlist(&waiting, &doctor, &start, &(start + 8 * SLOT));
+    client.cancel_appointment(&appointment_id);
+    client.expire_waitlist_offer(&1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #23)")]
+fn test_claim_expired_offer_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let waiting = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    let now = start - 24 * 60 * 60;
+    setup_ledger_time(&env, now);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.join_waitlist(&waiting, &doctor, &start, &(start + 8 * SLOT));
+    client.cancel_appointment(&appointment_id);
+
+    setup_ledger_time(&env, now + 3 * 60 * 60);
+    client.claim_waitlist_offer(&1, &in_person());
+}
+
+#[test]
+fn test_doctor_cancellation_refunds_in_full() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[&patient]);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(24 * 60 * 60), &5_000);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    // Inside the late-cancellation window, but the doctor is cancelling
+    client.doctor_cancel_appointment(&appointment_id, &CancellationReason::DoctorIllness);
+
+    assert_eq!(client.get_appointment_details(&appointment_id).status, AppointmentStatus::Canceled);
+    let cancellation = client.get_cancellation(&appointment_id).unwrap();
+    assert_eq!(cancellation.cancelled_by, doctor);
+    assert_eq!(cancellation.initiator, CancellationInitiator::Doctor);
+    assert_eq!(cancellation.reason, CancellationReason::DoctorIllness);
+    assert_eq!(cancellation.cancelled_at, start - 1000);
+
+    assert_eq!(token.balance(&patient), FEE);
+    assert_eq!(token.balance(&doctor), 0);
+}
+
+#[test]
+fn test_institution_cancellation() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let institution = Address::generate(&env);
+    setup_credentials(&env, &client, &doctor, Some(&institution));
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.institution_cancel_appointment(&institution, &appointment_id, &CancellationReason::FacilityClosure);
+
+    let cancellation = client.get_cancellation(&appointment_id).unwrap();
+    assert_eq!(cancellation.cancelled_by, institution);
+    assert_eq!(cancellation.initiator, CancellationInitiator::Institution);
+    assert_eq!(cancellation.reason, CancellationReason::FacilityClosure);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_unrelated_institution_cannot_cancel() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let institution = Address::generate(&env);
+    setup_credentials(&env, &client, &doctor, Some(&institution));
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    let other_institution = Address::generate(&env);
+    client.institution_cancel_appointment(&other_institution, &appointment_id, &CancellationReason::FacilityClosure);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #25)")]
+fn test_doctor_cannot_cancel_with_patient_reason() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.doctor_cancel_appointment(&appointment_id, &CancellationReason::PatientRequest);
+}
+
+const WEEK: u64 = 7 * 24 * 60 * 60;
+
+#[test]
+fn test_create_weekly_series() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    for week in 0..4 {
+        publish_availability(&client, &doctor, start + week * WEEK);
+    }
+
+    let series_id = client.create_appointment_series(&patient, &doctor, &start, &WEEK, &4, &in_person());
+
+    let series = client.get_series(&series_id);
+    assert_eq!(series.appointment_ids.len(), 4);
+    for (week, appointment_id) in series.appointment_ids.iter().enumerate() {
+        let appointment = client.get_appointment_details(&appointment_id);
+        assert_eq!(appointment.datetime, start + week as u64 * WEEK);
+        assert_eq!(appointment.series_id, Some(series_id));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_series_with_conflicting_occurrence_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let other_patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    for week in 0..3 {
+        publish_availability(&client, &doctor, start + week * WEEK);
+    }
+    client.create_appointment(&other_patient, &doctor, &(start + 2 * WEEK), &in_person());
+
+    client.create_appointment_series(&patient, &doctor, &start, &WEEK, &3, &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn test_series_interval_shorter_than_visit_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_appointment_series(&patient, &doctor, &start, &(SLOT / 2), &2, &in_person());
+}
+
+#[test]
+fn test_cancel_remaining_series() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    for week in 0..3 {
+        publish_availability(&client, &doctor, start + week * WEEK);
+    }
+    let series_id = client.create_appointment_series(&patient, &doctor, &start, &WEEK, &3, &in_person());
+    let ids = client.get_series(&series_id).appointment_ids;
+
+    // The first occurrence is cancelled on its own, the second has already taken place
+    client.cancel_appointment(&ids.get(0).unwrap());
+    setup_ledger_time(&env, start + WEEK);
+    client.complete_appointment(&ids.get(1).unwrap());
+
+    assert_eq!(client.cancel_remaining_series(&series_id), 1);
+    assert_eq!(client.get_appointment_details(&ids.get(0).unwrap()).status, AppointmentStatus::Canceled);
+    assert_eq!(client.get_appointment_details(&ids.get(1).unwrap()).status, AppointmentStatus::Completed);
+    assert_eq!(client.get_appointment_details(&ids.get(2).unwrap()).status, AppointmentStatus::Canceled);
+}
+
+#[test]
+fn test_delegate_books_and_cancels_for_patient() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let caregiver = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.authorize_delegate(&patient, &patient, &caregiver);
+    assert_eq!(client.get_delegates(&patient).len(), 1);
+
+    let appointment_id = client.create_appointment_on_behalf(&caregiver, &patient, &doctor, &start, &in_person());
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.patient, patient);
+    assert_eq!(appointment.booked_by, caregiver);
+
+    client.cancel_appointment_on_behalf(&caregiver, &appointment_id);
+    let cancellation = client.get_cancellation(&appointment_id).unwrap();
+    assert_eq!(cancellation.cancelled_by, caregiver);
+    assert_eq!(cancellation.initiator, CancellationInitiator::Patient);
+}
+
+#[test]
+fn test_delegate_pays_fee_with_own_signature() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let caregiver = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token = create_token(&env, &[&caregiver]);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(24 * 60 * 60), &5_000);
+    client.authorize_delegate(&patient, &patient, &caregiver);
+
+    // Only the caregiver signs: the booking and the fee transfer under it
+    let details = in_person();
+    let appointment_id = client
+        .mock_auths(&[MockAuth {
+            address: &caregiver,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "create_appointment_on_behalf",
+                args: (caregiver.clone(), patient.clone(), doctor.clone(), start, details.clone())
+                    .into_val(&env),
+                sub_invokes: &[MockAuthInvoke {
+                    contract: &token.address,
+                    fn_name: "transfer",
+                    args: (caregiver.clone(), contract_id.clone(), FEE).into_val(&env),
+                    sub_invokes: &[],
+                }],
+            },
+        }])
+        .create_appointment_on_behalf(&caregiver, &patient, &doctor, &start, &details);
+
+    assert_eq!(token.balance(&caregiver), 0);
+    assert_eq!(token.balance(&contract_id), FEE);
+    assert_eq!(client.get_payment(&appointment_id).unwrap().payer, caregiver);
+}
+
+#[test]
+fn test_guardian_authorizes_delegate() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let child = Address::generate(&env);
+    let parent = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.set_guardian(&child, &parent);
+    assert_eq!(client.get_guardian(&child), Some(parent.clone()));
+    client.authorize_delegate(&parent, &child, &parent);
+
+    let appointment_id = client.create_appointment_on_behalf(&parent, &child, &doctor, &start, &in_person());
+    assert_eq!(client.get_appointment_details(&appointment_id).booked_by, parent);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_stranger_cannot_authorize_delegate() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.authorize_delegate(&stranger, &patient, &stranger);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_revoked_delegate_cannot_book() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let caregiver = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.authorize_delegate(&patient, &patient, &caregiver);
+    client.revoke_delegate(&patient, &patient, &caregiver);
+
+    client.create_appointment_on_behalf(&caregiver, &patient, &doctor, &start, &in_person());
+}
+
+#[test]
+fn test_delegate_reschedule_accepted_by_doctor() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let caregiver = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.authorize_delegate(&patient, &patient, &caregiver);
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    client.propose_reschedule(&appointment_id, &caregiver, &(start + SLOT));
+    client.accept_reschedule(&appointment_id, &doctor);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.datetime, start + SLOT);
+    assert_eq!(appointment.reschedule_history.get(0).unwrap().proposed_by, caregiver);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_patient_cannot_accept_delegate_reschedule() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let caregiver = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.authorize_delegate(&patient, &patient, &caregiver);
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+
+    client.propose_reschedule(&appointment_id, &caregiver, &(start + SLOT));
+    client.accept_reschedule(&appointment_id, &patient);
+}
+
+// Deploys medical-record-management and wires it into `client`
+fn setup_medical_records<'a>(
+    env: &'a Env,
+    client: &contract::AppointmentSchedulingContractClient,
+) -> MedicalRecordsContractClient<'a> {
+    let records = MedicalRecordsContractClient::new(env, &env.register(MedicalRecordsContract, ()));
+    client.set_medical_records_contract(&records.address);
+    records
+}
+
+#[test]
+fn test_complete_appointment_with_record() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let records = setup_medical_records(&env, &client);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    records.grant_access(&patient, &doctor);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    setup_ledger_time(&env, start + SLOT);
+
+    let summary_hash = BytesN::from_array(&env, &[9u8; 32]);
+    let record_id = client.complete_appointment_with_record(
+        &appointment_id,
+        &summary_hash,
+        &String::from_str(&env, "Follow-up consultation"),
+    );
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.status, AppointmentStatus::Completed);
+    assert_eq!(appointment.medical_record_id, Some(record_id));
+
+    let record = records.get_medical_records(&patient).get(0).unwrap();
+    assert_eq!(record.id, record_id);
+    assert_eq!(record.doctor, doctor);
+    assert_eq!(record.data_id, summary_hash);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #31)")]
+fn test_complete_with_record_without_access_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    setup_medical_records(&env, &client);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.complete_appointment_with_record(
+        &appointment_id,
+        &BytesN::from_array(&env, &[9u8; 32]),
+        &String::from_str(&env, "Follow-up consultation"),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #30)")]
+fn test_complete_with_record_unconfigured_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.complete_appointment_with_record(
+        &appointment_id,
+        &BytesN::from_array(&env, &[9u8; 32]),
+        &String::from_str(&env, "Follow-up consultation"),
+    );
+}
+
+#[test]
+fn test_ratings_update_doctor_reputation() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let other_patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let first = client.create_appointment(&patient, &doctor, &start, &in_person());
+    let second = client.create_appointment(&other_patient, &doctor, &(start + SLOT), &in_person());
+    client.complete_appointment(&first);
+    client.complete_appointment(&second);
+
+    let review_hash = BytesN::from_array(&env, &[3u8; 32]);
+    client.rate_appointment(&first, &5, &review_hash);
+    client.rate_appointment(&second, &4, &review_hash);
+
+    let rating = client.get_rating(&first).unwrap();
+    assert_eq!(rating.score, 5);
+    assert_eq!(rating.doctor, doctor);
+
+    let reputation = client.get_doctor_reputation(&doctor);
+    assert_eq!(reputation.rating_count, 2);
+    assert_eq!(reputation.score_sum, 9);
+    assert_eq!(reputation.average, 450);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_rate_uncompleted_appointment_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.rate_appointment(&appointment_id, &5, &BytesN::from_array(&env, &[3u8; 32]));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #33)")]
+fn test_rate_appointment_twice_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.complete_appointment(&appointment_id);
+
+    let review_hash = BytesN::from_array(&env, &[3u8; 32]);
+    client.rate_appointment(&appointment_id, &5, &review_hash);
+    client.rate_appointment(&appointment_id, &1, &review_hash);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #32)")]
+fn test_rate_out_of_range_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.complete_appointment(&appointment_id);
+    client.rate_appointment(&appointment_id, &6, &BytesN::from_array(&env, &[3u8; 32]));
+}
+
+#[test]
+fn test_telemedicine_session_attendance() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &telemedicine(&env, SLOT));
+
+    setup_ledger_time(&env, start - 60);
+    client.join_session(&appointment_id, &doctor);
+    setup_ledger_time(&env, start + 120);
+    client.join_session(&appointment_id, &patient);
+    setup_ledger_time(&env, start + 1500);
+    client.end_session(&appointment_id, &doctor);
+
+    let session = client.get_session(&appointment_id).unwrap();
+    assert_eq!(session.doctor_joined_at, Some(start - 60));
+    assert_eq!(session.patient_joined_at, Some(start + 120));
+    assert_eq!(session.ended_at, Some(start + 1500));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #35)")]
+fn test_join_session_too_early_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 3600);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &telemedicine(&env, SLOT));
+    client.join_session(&appointment_id, &patient);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #34)")]
+fn test_join_session_for_in_person_visit_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    setup_ledger_time(&env, start);
+    client.join_session(&appointment_id, &patient);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_outsider_cannot_join_session() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &telemedicine(&env, SLOT));
+    setup_ledger_time(&env, start);
+    client.join_session(&appointment_id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #39)")]
+fn test_daily_capacity_reached_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    client.set_daily_capacity(&doctor, &2);
+
+    client.create_appointment(&Address::generate(&env), &doctor, &start, &in_person());
+    client.create_appointment(&Address::generate(&env), &doctor, &(start + SLOT), &in_person());
+    client.create_appointment(&Address::generate(&env), &doctor, &(start + 2 * SLOT), &in_person());
+}
+
+#[test]
+fn test_canceled_appointments_free_daily_capacity() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &doctor, start + WEEK);
+    client.set_daily_capacity(&doctor, &1);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    // Another day is unaffected
+    client.create_appointment(&patient, &doctor, &(start + WEEK), &in_person());
+
+    client.cancel_appointment(&appointment_id);
+    client.create_appointment(&patient, &doctor, &(start + SLOT), &in_person());
+    assert_eq!(client.get_daily_capacity(&doctor), Some(1));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #39)")]
+fn test_completed_appointments_keep_daily_capacity() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    client.set_daily_capacity(&doctor, &1);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.complete_appointment(&appointment_id);
+
+    client.create_appointment(&patient, &doctor, &(start + SLOT), &in_person());
+}
+
+#[test]
+fn test_reschedule_within_full_day() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &doctor, start + WEEK);
+    client.set_daily_capacity(&doctor, &1);
+
+    // Moving the day's only booking within the day does not count it twice
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.propose_reschedule(&appointment_id, &patient, &(start + 2 * SLOT));
+    client.accept_reschedule(&appointment_id, &doctor);
+
+    // Moving it to another day frees the first one
+    client.propose_reschedule(&appointment_id, &patient, &(start + WEEK));
+    client.accept_reschedule(&appointment_id, &doctor);
+    client.create_appointment(&patient, &doctor, &start, &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #40)")]
+fn test_patient_appointment_limit_reached_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_patient_appointment_limit(&2);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.create_appointment(&patient, &doctor, &(start + SLOT), &in_person());
+    client.create_appointment(&patient, &doctor, &(start + 2 * SLOT), &in_person());
+}
+
+#[test]
+fn test_completed_appointments_do_not_count_toward_patient_limit() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_patient_appointment_limit(&1);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.complete_appointment(&appointment_id);
+
+    client.create_appointment(&patient, &doctor, &(start + SLOT), &in_person());
+    assert_eq!(client.get_patient_appointment_limit(), Some(1));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #41)")]
+fn test_zero_daily_capacity_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    client.set_daily_capacity(&doctor, &0);
+}
+
+// Publishes an eight-slot urgent-only window for `doctor` starting at `start`
+fn publish_urgent_availability(
+    client: &contract::AppointmentSchedulingContractClient,
+    doctor: &Address,
+    start: u64,
+) -> u64 {
+    client.add_urgent_availability(doctor, &start, &(start + 8 * SLOT), &SLOT)
+}
+
+#[test]
+fn test_staff_books_urgent_only_slot() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let staff = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let institution = Address::generate(&env);
+    setup_credentials(&env, &client, &doctor, Some(&institution));
+    client.add_clinic_staff(&staff, &institution);
+    assert!(client.is_clinic_staff(&staff));
+    assert_eq!(client.get_clinic_staff_institution(&staff), Some(institution));
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_urgent_availability(&client, &doctor, start);
+
+    let appointment_id =
+        client.create_urgent_appointment(&staff, &patient, &doctor, &start, &in_person(), &Priority::Urgent);
+
+    let appointment = client.get_appointment_details(&appointment_id);
+    assert_eq!(appointment.priority, Priority::Urgent);
+    assert_eq!(appointment.booked_by, staff);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #42)")]
+fn test_routine_booking_in_urgent_only_slot_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_urgent_availability(&client, &doctor, start);
+
+    client.create_appointment(&patient, &doctor, &start, &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #43)")]
+fn test_unregistered_staff_cannot_book_urgent() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_urgent_availability(&client, &doctor, start);
+
+    let impostor = Address::generate(&env);
+    client.create_urgent_appointment(&impostor, &patient, &doctor, &start, &in_person(), &Priority::Emergency);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_staff_cannot_book_urgent_with_other_institution_doctor() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let staff = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let other_institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    credentials.add_institution(&other_institution);
+    client.add_clinic_staff(&staff, &other_institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_urgent_availability(&client, &doctor, start);
+
+    client.create_urgent_appointment(&staff, &patient, &doctor, &start, &in_person(), &Priority::Emergency);
+}
+
+#[test]
+fn test_urgent_booking_displaces_routine_appointment() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let staff = Address::generate(&env);
+    let routine_patient = Address::generate(&env);
+    let urgent_patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let institution = Address::generate(&env);
+    setup_credentials(&env, &client, &doctor, Some(&institution));
+    client.add_clinic_staff(&staff, &institution);
+
+    let token = create_token(&env, &[&routine_patient, &staff]);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &0, &0);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let routine_id = client.create_appointment(&routine_patient, &doctor, &start, &in_person());
+    let urgent_id =
+        client.create_urgent_appointment(&staff, &urgent_patient, &doctor, &start, &in_person(), &Priority::Urgent);
+
+    let routine = client.get_appointment_details(&routine_id);
+    assert_eq!(routine.status, AppointmentStatus::Canceled);
+    let cancellation = client.get_cancellation(&routine_id).unwrap();
+    assert_eq!(cancellation.initiator, CancellationInitiator::ClinicStaff);
+    assert_eq!(cancellation.reason, CancellationReason::Displaced);
+    assert_eq!(token.balance(&routine_patient), FEE);
+
+    assert_eq!(client.get_appointment_details(&urgent_id).status, AppointmentStatus::Scheduled);
+    assert_eq!(client.get_payment(&urgent_id).unwrap().payer, staff);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_urgent_booking_does_not_displace_equal_priority() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let staff = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let institution = Address::generate(&env);
+    setup_credentials(&env, &client, &doctor, Some(&institution));
+    client.add_clinic_staff(&staff, &institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    client.create_urgent_appointment(&staff, &first, &doctor, &start, &in_person(), &Priority::Urgent);
+    client.create_urgent_appointment(&staff, &second, &doctor, &start, &in_person(), &Priority::Urgent);
+}
+
+#[test]
+fn test_group_session_flow() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let third = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let session_id = client.create_group_session(&doctor, &start, &in_person(), &3);
+    client.join_group_session(&session_id, &first);
+    client.join_group_session(&session_id, &second);
+    client.join_group_session(&session_id, &third);
+    client.leave_group_session(&session_id, &third);
+
+    setup_ledger_time(&env, start);
+    client.check_in_group_session(&session_id, &first);
+    client.complete_group_session(&session_id);
+
+    let session = client.get_group_session(&session_id);
+    assert_eq!(session.status, AppointmentStatus::Completed);
+    assert_eq!(session.attendees.len(), 2);
+    assert!(session.checked_in.contains(&first));
+    assert!(!session.checked_in.contains(&second));
+    assert_eq!(session.completed_at, Some(start));
+    assert_eq!(client.get_patient_group_sessions(&third).len(), 1);
+}
+
+#[test]
+fn test_group_session_seat_fees() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let token = create_token(&env, &[&first, &second]);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &0, &0);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let session_id = client.create_group_session(&doctor, &start, &in_person(), &3);
+    client.join_group_session(&session_id, &first);
+    client.join_group_session(&session_id, &second);
+    assert_eq!(token.balance(&first), 0);
+
+    client.leave_group_session(&session_id, &second);
+    assert_eq!(token.balance(&second), FEE);
+
+    setup_ledger_time(&env, start);
+    client.complete_group_session(&session_id);
+    assert_eq!(token.balance(&doctor), FEE);
+    let payment = client.get_seat_payment(&session_id, &first).unwrap();
+    assert_eq!(payment.status, PaymentStatus::Released);
+}
+
+#[test]
+fn test_cancel_group_session_refunds_attendees() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let token = create_token(&env, &[&first, &second]);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &(24 * 60 * 60), &5_000);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    client.set_daily_capacity(&doctor, &1);
+
+    let session_id = client.create_group_session(&doctor, &start, &in_person(), &3);
+    client.join_group_session(&session_id, &first);
+    client.join_group_session(&session_id, &second);
+
+    // A doctor's cancellation refunds in full, even late
+    client.cancel_group_session(&session_id);
+    assert_eq!(client.get_group_session(&session_id).status, AppointmentStatus::Canceled);
+    assert_eq!(token.balance(&first), FEE);
+    assert_eq!(token.balance(&second), FEE);
+    env.as_contract(&contract_id, || {
+        let key = DataKey::DoctorGroupSessions(doctor.clone(), start / (24 * 60 * 60));
+        assert!(!env.storage().persistent().has(&key));
+    });
+
+    // The slot and the day's capacity are free again
+    client.create_appointment(&first, &doctor, &start, &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #39)")]
+fn test_group_session_counts_toward_daily_capacity() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    client.set_daily_capacity(&doctor, &1);
+
+    client.create_group_session(&doctor, &start, &in_person(), &10);
+    client.create_appointment(&Address::generate(&env), &doctor, &(start + SLOT), &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #46)")]
+fn test_join_full_group_session_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let session_id = client.create_group_session(&doctor, &start, &in_person(), &1);
+    client.join_group_session(&session_id, &Address::generate(&env));
+    client.join_group_session(&session_id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_group_session_blocks_individual_booking() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_group_session(&doctor, &start, &in_person(), &10);
+    client.create_appointment(&Address::generate(&env), &doctor, &start, &in_person());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #47)")]
+fn test_non_attendee_cannot_check_in_group_session() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let session_id = client.create_group_session(&doctor, &start, &in_person(), &10);
+    setup_ledger_time(&env, start);
+    client.check_in_group_session(&session_id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #52)")]
+fn test_double_check_in_group_session_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let session_id = client.create_group_session(&doctor, &start, &in_person(), &10);
+    client.join_group_session(&session_id, &patient);
+    setup_ledger_time(&env, start);
+    client.check_in_group_session(&session_id, &patient);
+    client.check_in_group_session(&session_id, &patient);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #48)")]
+fn test_group_session_without_seats_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    client.create_group_session(&doctor, &start, &in_person(), &0);
+}
+
+// Registers a second doctor with the credentials contract, verified by `institution`
+fn register_colleague(
+    env: &Env,
+    credentials: &DoctorCredentialsClient,
+    doctor: &Address,
+    institution: &Address,
+) {
+    credentials.register_doctor(
+        doctor,
+        &String::from_str(env, "Dr. John Roe"),
+        &String::from_str(env, "Cardiology"),
+        &Bytes::from_array(env, &[2u8; 32]),
+    );
+    credentials.verify_doctor(doctor, institution);
+}
+
+#[test]
+fn test_handover_to_covering_doctor() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let other_patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let colleague = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    register_colleague(&env, &credentials, &colleague, &institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &colleague, start);
+
+    let first = client.create_appointment(&patient, &doctor, &start, &in_person());
+    let second = client.create_appointment(&other_patient, &doctor, &(start + SLOT), &in_person());
+
+    let handover_id = client.propose_handover(&institution, &doctor, &colleague, &vec![&env, first, second, first]);
+    assert_eq!(client.get_handover(&handover_id).appointment_ids, vec![&env, first, second]);
+
+    client.accept_handover(&handover_id);
+    client.decline_handover(&handover_id, &second);
+
+    assert_eq!(client.get_appointment_details(&first).doctor, colleague);
+    assert_eq!(client.get_appointments(&colleague).len(), 2);
+    assert_eq!(client.get_appointments(&doctor).len(), 0);
+
+    let declined = client.get_appointment_details(&second);
+    assert_eq!(declined.status, AppointmentStatus::Canceled);
+    let cancellation = client.get_cancellation(&second).unwrap();
+    assert_eq!(cancellation.initiator, CancellationInitiator::Institution);
+    assert_eq!(cancellation.cancelled_by, institution);
+    assert_eq!(cancellation.reason, CancellationReason::DoctorUnavailable);
+    assert!(client.get_handover(&handover_id).accepted_at.is_some());
+
+    // Both bookings, made 1000 and 2800 seconds ahead, moved with the handover
+    let stats = client.get_doctor_stats(&doctor);
+    assert_eq!(stats.booked, 0);
+    assert_eq!(stats.total_lead_time, 0);
+    assert_eq!(stats.average_lead_time, 0);
+    let stats = client.get_doctor_stats(&colleague);
+    assert_eq!(stats.booked, 2);
+    assert_eq!(stats.total_lead_time, 3800);
+    assert_eq!(stats.average_lead_time, 1900);
+    assert_eq!(stats.cancelled_by_provider, 1);
+}
+
+#[test]
+fn test_patient_declines_after_handover_accepted() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let colleague = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    register_colleague(&env, &credentials, &colleague, &institution);
+
+    let token = create_token(&env, &[&patient]);
+    client.set_consultation_fee(&doctor, &token.address, &FEE, &0, &0);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &colleague, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    let handover_id = client.propose_handover(&doctor, &doctor, &colleague, &vec![&env, appointment_id]);
+    client.accept_handover(&handover_id);
+
+    client.decline_handover(&handover_id, &appointment_id);
+    assert_eq!(client.get_appointment_details(&appointment_id).status, AppointmentStatus::Canceled);
+    assert_eq!(client.get_cancellation(&appointment_id).unwrap().initiator, CancellationInitiator::Doctor);
+    assert_eq!(token.balance(&patient), FEE);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_decline_handover_before_acceptance_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let colleague = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    register_colleague(&env, &credentials, &colleague, &institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    let handover_id = client.propose_handover(&doctor, &doctor, &colleague, &vec![&env, appointment_id]);
+
+    client.decline_handover(&handover_id, &appointment_id);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_handover_to_other_institution_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let other_institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    credentials.add_institution(&other_institution);
+    register_colleague(&env, &credentials, &outsider, &other_institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.propose_handover(&doctor, &doctor, &outsider, &vec![&env, appointment_id]);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #14)")]
+fn test_handover_of_too_many_appointments_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let colleague = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    register_colleague(&env, &credentials, &colleague, &institution);
+
+    let mut appointment_ids = vec![&env];
+    for id in 1..=51u64 {
+        appointment_ids.push_back(id);
+    }
+    client.propose_handover(&doctor, &doctor, &colleague, &appointment_ids);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_accept_handover_with_conflict_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let colleague = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    register_colleague(&env, &credentials, &colleague, &institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &colleague, start);
+
+    let appointment_id = client.create_appointment(&Address::generate(&env), &doctor, &start, &in_person());
+    client.create_appointment(&Address::generate(&env), &colleague, &start, &in_person());
+
+    let handover_id = client.propose_handover(&doctor, &doctor, &colleague, &vec![&env, appointment_id]);
+    client.accept_handover(&handover_id);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_accept_handover_outside_covering_availability_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let colleague = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    register_colleague(&env, &credentials, &colleague, &institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&Address::generate(&env), &doctor, &start, &in_person());
+
+    let handover_id = client.propose_handover(&doctor, &doctor, &colleague, &vec![&env, appointment_id]);
+    client.accept_handover(&handover_id);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #39)")]
+fn test_accept_handover_over_daily_capacity_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let colleague = Address::generate(&env);
+    let institution = Address::generate(&env);
+    let credentials = setup_credentials(&env, &client, &doctor, Some(&institution));
+    register_colleague(&env, &credentials, &colleague, &institution);
+
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+    publish_availability(&client, &colleague, start);
+    client.set_daily_capacity(&colleague, &1);
+
+    let appointment_id = client.create_appointment(&Address::generate(&env), &doctor, &start, &in_person());
+    client.create_appointment(&Address::generate(&env), &colleague, &(start + SLOT), &in_person());
+
+    let handover_id = client.propose_handover(&doctor, &doctor, &colleague, &vec![&env, appointment_id]);
+    client.accept_handover(&handover_id);
+}
+
+#[test]
+fn test_patient_attaches_intake_documents() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    let questionnaire = BytesN::from_array(&env, &[4u8; 32]);
+    let referral = BytesN::from_array(&env, &[5u8; 32]);
+    client.add_attachment(&appointment_id, &AttachmentKind::IntakeQuestionnaire, &questionnaire);
+    client.add_attachment(&appointment_id, &AttachmentKind::ReferralLetter, &referral);
+
+    let attachments = client.get_attachments(&appointment_id, &doctor);
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments.get(0).unwrap().document_hash, questionnaire);
+    assert_eq!(attachments.get(1).unwrap().kind, AttachmentKind::ReferralLetter);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_only_doctor_lists_attachments() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.get_attachments(&appointment_id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_attachments_lock_after_completion() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.complete_appointment(&appointment_id);
+    client.add_attachment(&appointment_id, &AttachmentKind::Other, &BytesN::from_array(&env, &[4u8; 32]));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #50)")]
+fn test_empty_attachment_hash_fails() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let appointment_id = client.create_appointment(&patient, &doctor, &start, &in_person());
+    client.add_attachment(&appointment_id, &AttachmentKind::Other, &BytesN::from_array(&env, &[0u8; 32]));
+}
+
+#[test]
+fn test_doctor_stats_track_outcomes() {
+    let env = create_env();
+    env.mock_all_auths();
+    let contract_id = register_contract(&env);
+    let client = create_client(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let doctor = Address::generate(&env);
+    let start: u64 = 1746000000;
+    setup_ledger_time(&env, start - 1000);
+    publish_availability(&client, &doctor, start);
+
+    let completed = client.create_appointment(&Address::generate(&env), &doctor, &start, &in_person());
+    let by_patient = client.create_appointment(&Address::generate(&env), &doctor, &(start + SLOT), &in_person());
+    let by_doctor = client.create_appointment(&Address::generate(&env), &doctor, &(start + 2 * SLOT), &in_person());
+    let no_show = client.create_appointment(&Address::generate(&env), &doctor, &(start + 3 * SLOT), &in_person());
+
+    client.complete_appointment(&completed);
+    client.cancel_appointment(&by_patient);
+    client.doctor_cancel_appointment(&by_doctor, &CancellationReason::DoctorIllness);
+    setup_ledger_time(&env, start + 4 * SLOT);
+    client.mark_no_show(&no_show);
+
+    let stats = client.get_doctor_stats(&doctor);
+    assert_eq!(stats.booked, 4);
+    assert_eq!(stats.completed, 1);
+    assert_eq!(stats.cancelled_by_patient, 1);
+    assert_eq!(stats.cancelled_by_provider, 1);
+    assert_eq!(stats.no_shows, 1);
+    // Lead times of 1000, 1000 + SLOT, 1000 + 2 * SLOT and 1000 + 3 * SLOT seconds
+    assert_eq!(stats.total_lead_time, 4000 + 6 * SLOT);
+    assert_eq!(stats.average_lead_time, 1000 + 3 * SLOT / 2);
+}
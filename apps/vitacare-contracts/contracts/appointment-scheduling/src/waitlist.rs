@@ -0,0 +1,120 @@
+use soroban_sdk::{symbol_short, vec, Address, Env, Vec};
+
+use crate::error::Error;
+use crate::storage::days_spanned;
+use crate::storage_types::{DataKey, SlotOffer, VisitDetails, WaitlistEntry};
+
+/// How long a waitlisted patient has to claim a freed slot.
+pub const OFFER_CLAIM_WINDOW: u64 = 2 * 60 * 60;
+
+pub fn get_waitlist(env: &Env, doctor: &Address) -> Vec<WaitlistEntry> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Waitlist(doctor.clone()))
+        .unwrap_or_else(|| vec![env])
+}
+
+pub fn save_waitlist(env: &Env, doctor: &Address, waitlist: &Vec<WaitlistEntry>) {
+    env.storage().persistent().set(&DataKey::Waitlist(doctor.clone()), waitlist);
+}
+
+pub fn get_offer(env: &Env, offer_id: u64) -> Result<SlotOffer, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SlotOffer(offer_id))
+        .ok_or(Error::OfferNotFound)
+}
+
+/// Offers of `doctor` that can still be claimed and take up part of `day`,
+/// counted in days since the epoch. These hold their slot.
+pub fn get_active_offers(env: &Env, doctor: &Address, day: u64) -> Vec<SlotOffer> {
+    let now = env.ledger().timestamp();
+    let mut offers = vec![env];
+    for offer_id in get_offer_ids(env, doctor, day).iter() {
+        if let Ok(offer) = get_offer(env, offer_id)
+            && offer.expires_at > now
+        {
+            offers.push_back(offer);
+        }
+    }
+    offers
+}
+
+pub fn remove_offer(env: &Env, offer: &SlotOffer) {
+    for day in days_spanned(offer.datetime, offer.datetime + offer.details.duration) {
+        let key = DataKey::DoctorOffers(offer.doctor.clone(), day);
+        let mut offer_ids = get_offer_ids(env, &offer.doctor, day);
+        if let Some(index) = offer_ids.first_index_of(offer.id) {
+            offer_ids.remove(index);
+        }
+        if offer_ids.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &offer_ids);
+        }
+    }
+    env.storage().persistent().remove(&DataKey::SlotOffer(offer.id));
+}
+
+/// Offer a freed slot to the first waiting patient whose preferred range
+/// covers `datetime`. That patient leaves the waitlist; entries whose range
+/// has already passed are dropped on the way.
+pub fn offer_slot(env: &Env, doctor: &Address, datetime: u64, details: &VisitDetails) -> Option<u64> {
+    let now = env.ledger().timestamp();
+    if datetime <= now {
+        return None;
+    }
+
+    let waitlist = get_waitlist(env, doctor);
+    let mut remaining = vec![env];
+    let mut chosen: Option<WaitlistEntry> = None;
+    for entry in waitlist.iter() {
+        if entry.latest < now {
+            continue;
+        }
+        if chosen.is_none() && entry.earliest <= datetime && datetime <= entry.latest {
+            chosen = Some(entry);
+        } else {
+            remaining.push_back(entry);
+        }
+    }
+    if remaining.len() != waitlist.len() {
+        save_waitlist(env, doctor, &remaining);
+    }
+    let entry = chosen?;
+
+    let offer_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextOfferId)
+        .unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextOfferId, &(offer_id + 1));
+
+    let offer = SlotOffer {
+        id: offer_id,
+        doctor: doctor.clone(),
+        patient: entry.patient.clone(),
+        datetime,
+        details: details.clone(),
+        expires_at: (now + OFFER_CLAIM_WINDOW).min(datetime),
+    };
+    env.storage().persistent().set(&DataKey::SlotOffer(offer_id), &offer);
+
+    for day in days_spanned(datetime, datetime + details.duration) {
+        let mut offer_ids = get_offer_ids(env, doctor, day);
+        offer_ids.push_back(offer_id);
+        env.storage().persistent().set(&DataKey::DoctorOffers(doctor.clone(), day), &offer_ids);
+    }
+
+    env.events()
+        .publish((symbol_short!("offer"), entry.patient), (offer_id, datetime, offer.expires_at));
+
+    Some(offer_id)
+}
+
+fn get_offer_ids(env: &Env, doctor: &Address, day: u64) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DoctorOffers(doctor.clone(), day))
+        .unwrap_or_else(|| vec![env])
+}
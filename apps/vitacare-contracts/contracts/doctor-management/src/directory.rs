@@ -0,0 +1,53 @@
+use soroban_sdk::{Address, Env, String, Vec};
+
+use crate::errors::DoctorError;
+use crate::storage::DoctorStorage;
+use crate::types::{DoctorPage, MAX_PAGE_SIZE, MAX_SPECIALIZATION_LEN};
+
+/// Lowercases `specialization` and trims surrounding whitespace, so that
+/// "Cardiology" and " cardiology " share one directory entry.
+pub fn normalize_specialization(env: &Env, specialization: &String) -> Result<String, DoctorError> {
+    let len = specialization.len() as usize;
+    if len > MAX_SPECIALIZATION_LEN {
+        return Err(DoctorError::InvalidInput);
+    }
+
+    let mut buf = [0u8; MAX_SPECIALIZATION_LEN];
+    specialization.copy_into_slice(&mut buf[..len]);
+    buf[..len].make_ascii_lowercase();
+
+    let mut start = 0;
+    let mut end = len;
+    while start < end && buf[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && buf[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    Ok(String::from_bytes(env, &buf[start..end]))
+}
+
+/// Loads the profiles at positions `cursor..cursor + limit` of `wallets`.
+pub fn page(
+    env: &Env,
+    wallets: &Vec<Address>,
+    cursor: u32,
+    limit: u32,
+) -> Result<DoctorPage, DoctorError> {
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(DoctorError::InvalidPagination);
+    }
+
+    let storage = DoctorStorage::new(env);
+    let end = wallets.len().min(cursor.saturating_add(limit));
+
+    let mut doctors = Vec::new(env);
+    for index in cursor..end {
+        if let Ok(profile) = storage.get_doctor(&wallets.get_unchecked(index)) {
+            doctors.push_back(profile);
+        }
+    }
+
+    let next_cursor = if end < wallets.len() { Some(end) } else { None };
+    Ok(DoctorPage { doctors, next_cursor })
+}
@@ -8,4 +8,11 @@ pub enum DoctorError {
     DoctorAlreadyExists = 2,
     InvalidInput = 3,
     Unauthorized = 4,
+    NotInitialized = 5,
+    AlreadyInitialized = 6,
+    InstitutionNotVerified = 7,
+    InvalidPagination = 8,
+    AffiliationNotFound = 9,
+    AffiliationExists = 10,
+    InvalidStatusTransition = 11,
 } 
\ No newline at end of file
@@ -0,0 +1,65 @@
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::errors::DoctorError;
+use crate::storage::DoctorStorage;
+use crate::types::{DoctorProfile, ProfileField, ProfileHistoryPage, ProfileVersion, MAX_PAGE_SIZE};
+
+/// Saves `profile` and appends it to the doctor's version log.
+pub fn save_version(env: &Env, profile: &DoctorProfile, changed_fields: Vec<ProfileField>, signer: &Address) {
+    let storage = DoctorStorage::new(env);
+    let version = storage.get_version_count(&profile.wallet);
+    storage.save_doctor(&profile.wallet, profile);
+    storage.save_version(
+        &profile.wallet,
+        &ProfileVersion {
+            version,
+            changed_fields,
+            changed_at: profile.updated_at,
+            signer: signer.clone(),
+            profile: profile.clone(),
+        },
+    );
+}
+
+/// The profile of `wallet` as it stood at `timestamp`, i.e. the last version
+/// recorded at or before it.
+pub fn profile_at(env: &Env, wallet: &Address, timestamp: u64) -> Result<DoctorProfile, DoctorError> {
+    let storage = DoctorStorage::new(env);
+
+    // Versions are appended in time order, so the first version after
+    // `timestamp` can be found by bisection.
+    let mut low = 0;
+    let mut high = storage.get_version_count(wallet);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if storage.get_version(wallet, mid)?.changed_at <= timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    if low == 0 {
+        return Err(DoctorError::DoctorNotFound);
+    }
+    Ok(storage.get_version(wallet, low - 1)?.profile)
+}
+
+/// Loads versions `cursor..cursor + limit` of `wallet`'s log.
+pub fn page(env: &Env, wallet: &Address, cursor: u32, limit: u32) -> Result<ProfileHistoryPage, DoctorError> {
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(DoctorError::InvalidPagination);
+    }
+
+    let storage = DoctorStorage::new(env);
+    let count = storage.get_version_count(wallet);
+    let end = count.min(cursor.saturating_add(limit));
+
+    let mut versions = Vec::new(env);
+    for version in cursor..end {
+        versions.push_back(storage.get_version(wallet, version)?);
+    }
+
+    let next_cursor = if end < count { Some(end) } else { None };
+    Ok(ProfileHistoryPage { versions, next_cursor })
+}
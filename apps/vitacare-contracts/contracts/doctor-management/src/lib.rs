@@ -1,13 +1,19 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, Address, Env, String, panic_with_error};
+use soroban_sdk::{contract, contractimpl, vec, Address, Env, String, Vec, panic_with_error};
 
 mod types;
 mod storage;
 mod errors;
+mod registry;
+mod directory;
+mod history;
 mod test;
 
-use types::DoctorProfile;
+use types::{
+    Affiliation, AffiliationStatus, DoctorPage, DoctorProfile, DoctorStatus, ProfileField,
+    ProfileHistoryPage, MAX_AFFILIATION_LEAD_TIME,
+};
 use storage::DoctorStorage;
 use errors::DoctorError;
 
@@ -16,13 +22,45 @@ pub struct DoctorManagementContract;
 
 #[contractimpl]
 impl DoctorManagementContract {
+    /// Sets the admin and the institution registry that doctor affiliations
+    /// are checked against.
+    pub fn initialize(env: &Env, admin: Address, institution_registry: Address) -> Result<(), DoctorError> {
+        let storage = DoctorStorage::new(env);
+        if storage.has_admin() {
+            panic_with_error!(env, DoctorError::AlreadyInitialized);
+        }
+
+        admin.require_auth();
+        storage.set_admin(&admin);
+        storage.set_institution_registry(&institution_registry);
+        Ok(())
+    }
+
+    pub fn set_institution_registry(env: &Env, institution_registry: Address) -> Result<(), DoctorError> {
+        let storage = DoctorStorage::new(env);
+        storage.get_admin()?.require_auth();
+        storage.set_institution_registry(&institution_registry);
+        Ok(())
+    }
+
+    pub fn get_institution_registry(env: &Env) -> Result<Address, DoctorError> {
+        DoctorStorage::new(env).get_institution_registry()
+    }
+
+    /// Creates the profile of `wallet` with an active affiliation to
+    /// `institution_wallet`. Both the doctor and the institution must sign,
+    /// and the institution must be verified in the institution registry.
     pub fn create_doctor_profile(
         env: &Env,
         wallet: Address,
         name: String,
         specialization: String,
         institution_wallet: Address,
+        role: String,
     ) -> Result<(), DoctorError> {
+        wallet.require_auth();
+        institution_wallet.require_auth();
+
         let storage = DoctorStorage::new(env);
         
         // Check if doctor profile already exists
@@ -30,27 +68,124 @@ impl DoctorManagementContract {
             panic_with_error!(env, DoctorError::DoctorAlreadyExists);
         }
 
-        let timestamp = env.ledger().timestamp();
-        let profile = DoctorProfile {
-            wallet: wallet.clone(),
-            name,
-            specialization,
-            institution_wallet,
-            metadata: String::from_str(env, ""),
-            created_at: timestamp,
-            updated_at: timestamp,
+        registry::require_verified_institution(env, &institution_wallet)?;
+        let specialization_key = directory::normalize_specialization(env, &specialization)?;
+
+        let affiliation = Affiliation {
+            institution: institution_wallet.clone(),
+            role,
+            start_date: env.ledger().timestamp(),
+            end_date: None,
+            status: AffiliationStatus::Active,
         };
+        let profile = DoctorProfile::new(env, wallet.clone(), name, specialization, vec![env, affiliation]);
+
+        history::save_version(env, &profile, vec![env, ProfileField::Created], &wallet);
+        storage.add_to_specialization_index(&specialization_key, &wallet);
+        storage.add_to_institution_index(&institution_wallet, &wallet);
+        Ok(())
+    }
+
+    /// Records that `wallet` works at `institution` as `role` since
+    /// `start_date`, which may lie at most `MAX_AFFILIATION_LEAD_TIME` ahead.
+    /// The affiliation stays pending until the institution confirms it with
+    /// `confirm_affiliation`.
+    pub fn request_affiliation(
+        env: &Env,
+        wallet: Address,
+        institution: Address,
+        role: String,
+        start_date: u64,
+    ) -> Result<(), DoctorError> {
+        wallet.require_auth();
+
+        if start_date == 0 || start_date > env.ledger().timestamp() + MAX_AFFILIATION_LEAD_TIME {
+            return Err(DoctorError::InvalidInput);
+        }
 
-        storage.save_doctor(&wallet, &profile);
+        let storage = DoctorStorage::new(env);
+        let mut profile = storage.get_doctor(&wallet)?;
+        if profile.open_affiliation(&institution).is_some() {
+            return Err(DoctorError::AffiliationExists);
+        }
+        registry::require_verified_institution(env, &institution)?;
+
+        profile.affiliations.push_back(Affiliation {
+            institution,
+            role,
+            start_date,
+            end_date: None,
+            status: AffiliationStatus::Pending,
+        });
+        profile.updated_at = env.ledger().timestamp();
+        history::save_version(env, &profile, vec![env, ProfileField::Affiliations], &wallet);
         Ok(())
     }
 
+    /// Confirms the pending affiliation of `wallet` with `institution`, which
+    /// must sign. Only confirmed affiliations count for the directory.
+    pub fn confirm_affiliation(env: &Env, institution: Address, wallet: Address) -> Result<(), DoctorError> {
+        institution.require_auth();
+
+        let storage = DoctorStorage::new(env);
+        let mut profile = storage.get_doctor(&wallet)?;
+        let index = profile
+            .open_affiliation(&institution)
+            .ok_or(DoctorError::AffiliationNotFound)?;
+        let mut affiliation = profile.affiliations.get_unchecked(index);
+        if affiliation.status != AffiliationStatus::Pending {
+            return Err(DoctorError::AffiliationNotFound);
+        }
+
+        affiliation.status = AffiliationStatus::Active;
+        profile.affiliations.set(index, affiliation);
+        profile.updated_at = env.ledger().timestamp();
+        history::save_version(env, &profile, vec![env, ProfileField::Affiliations], &institution);
+        storage.add_to_institution_index(&institution, &wallet);
+        Ok(())
+    }
+
+    /// Ends the pending or active affiliation of `wallet` with `institution`.
+    /// Either the doctor or the institution may call this as `caller`; the
+    /// affiliation is kept in the profile with its end date.
+    pub fn end_affiliation(
+        env: &Env,
+        caller: Address,
+        wallet: Address,
+        institution: Address,
+    ) -> Result<(), DoctorError> {
+        caller.require_auth();
+        if caller != wallet && caller != institution {
+            return Err(DoctorError::Unauthorized);
+        }
+
+        let storage = DoctorStorage::new(env);
+        let mut profile = storage.get_doctor(&wallet)?;
+        let index = profile
+            .open_affiliation(&institution)
+            .ok_or(DoctorError::AffiliationNotFound)?;
+
+        let timestamp = env.ledger().timestamp();
+        let mut affiliation = profile.affiliations.get_unchecked(index);
+        affiliation.status = AffiliationStatus::Ended;
+        affiliation.end_date = Some(timestamp);
+        profile.affiliations.set(index, affiliation);
+        profile.updated_at = timestamp;
+        history::save_version(env, &profile, vec![env, ProfileField::Affiliations], &caller);
+        storage.remove_from_institution_index(&institution, &wallet);
+        Ok(())
+    }
+
+    /// Updates the given fields of `wallet`'s profile. Every update is
+    /// appended to the profile's version log.
     pub fn update_doctor_profile(
         env: &Env,
         wallet: Address,
         specialization: Option<String>,
         metadata: Option<String>,
     ) -> Result<(), DoctorError> {
+        wallet.require_auth();
+
         let storage = DoctorStorage::new(env);
         
         // Check if doctor profile exists
@@ -59,17 +194,35 @@ impl DoctorManagementContract {
         }
 
         let mut profile = storage.get_doctor(&wallet)?;
+        let mut changed_fields = Vec::new(env);
         
-        if let Some(spec) = specialization {
+        if let Some(spec) = specialization
+            && spec != profile.specialization
+        {
+            let old_key = directory::normalize_specialization(env, &profile.specialization)?;
+            let new_key = directory::normalize_specialization(env, &spec)?;
+            if old_key != new_key {
+                storage.remove_from_specialization_index(&old_key, &wallet);
+                storage.add_to_specialization_index(&new_key, &wallet);
+            }
             profile.specialization = spec;
+            changed_fields.push_back(ProfileField::Specialization);
         }
         
-        if let Some(meta) = metadata {
+        if let Some(meta) = metadata
+            && meta != profile.metadata
+        {
             profile.metadata = meta;
+            changed_fields.push_back(ProfileField::Metadata);
+        }
+
+        // Nothing to record when every field already has the requested value
+        if changed_fields.is_empty() {
+            return Ok(());
         }
         
         profile.updated_at = env.ledger().timestamp();
-        storage.save_doctor(&wallet, &profile);
+        history::save_version(env, &profile, changed_fields, &wallet);
         
         Ok(())
     }
@@ -81,4 +234,97 @@ impl DoctorManagementContract {
         }
         storage.get_doctor(&wallet)
     }
+
+    /// Changes the practice status of `wallet`. The doctor may move between
+    /// Active, OnLeave and Retired. Only the admin or a verified institution
+    /// the doctor is affiliated with may suspend the doctor or lift a
+    /// suspension, and the doctor cannot change their status while suspended.
+    pub fn set_doctor_status(
+        env: &Env,
+        caller: Address,
+        wallet: Address,
+        status: DoctorStatus,
+        reason: String,
+    ) -> Result<(), DoctorError> {
+        caller.require_auth();
+
+        let storage = DoctorStorage::new(env);
+        let mut profile = storage.get_doctor(&wallet)?;
+        if profile.status == status {
+            return Err(DoctorError::InvalidStatusTransition);
+        }
+
+        let suspending = status == DoctorStatus::Suspended;
+        let reinstating = profile.status == DoctorStatus::Suspended;
+        if suspending || reinstating {
+            if caller != storage.get_admin()? {
+                if !profile.is_affiliated_with(&caller) {
+                    return Err(DoctorError::Unauthorized);
+                }
+                registry::require_verified_institution(env, &caller)?;
+            }
+            if reinstating && status != DoctorStatus::Active {
+                return Err(DoctorError::InvalidStatusTransition);
+            }
+        } else if caller != wallet {
+            return Err(DoctorError::Unauthorized);
+        }
+
+        let timestamp = env.ledger().timestamp();
+        profile.status = status;
+        profile.status_reason = reason;
+        profile.status_changed_at = timestamp;
+        profile.updated_at = timestamp;
+        history::save_version(env, &profile, vec![env, ProfileField::Status], &caller);
+        Ok(())
+    }
+
+    /// Returns the practice status of `wallet`, for contracts that only need
+    /// to know whether the doctor may practice.
+    pub fn get_doctor_status(env: &Env, wallet: Address) -> Result<DoctorStatus, DoctorError> {
+        Ok(DoctorStorage::new(env).get_doctor(&wallet)?.status)
+    }
+
+    /// Returns the profile of `wallet` as it stood at `timestamp`. Fails with
+    /// `DoctorNotFound` for times before the profile was created.
+    pub fn get_doctor_profile_at(env: &Env, wallet: Address, timestamp: u64) -> Result<DoctorProfile, DoctorError> {
+        history::profile_at(env, &wallet, timestamp)
+    }
+
+    /// Returns up to `limit` entries of the version log of `wallet`, oldest
+    /// first, starting at version `cursor`.
+    pub fn get_profile_history(
+        env: &Env,
+        wallet: Address,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<ProfileHistoryPage, DoctorError> {
+        history::page(env, &wallet, cursor, limit)
+    }
+
+    /// Lists doctors whose specialization matches `specialization`, ignoring
+    /// case and surrounding whitespace. Pass the returned `next_cursor` to
+    /// fetch the following page.
+    pub fn list_doctors_by_specialization(
+        env: &Env,
+        specialization: String,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<DoctorPage, DoctorError> {
+        let key = directory::normalize_specialization(env, &specialization)?;
+        let wallets = DoctorStorage::new(env).get_specialization_index(&key);
+        directory::page(env, &wallets, cursor, limit)
+    }
+
+    /// Lists doctors with an active affiliation to `institution_wallet`, in
+    /// order of confirmation.
+    pub fn list_doctors_by_institution(
+        env: &Env,
+        institution_wallet: Address,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<DoctorPage, DoctorError> {
+        let wallets = DoctorStorage::new(env).get_institution_index(&institution_wallet);
+        directory::page(env, &wallets, cursor, limit)
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,32 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env, String};
+
+use crate::errors::DoctorError;
+use crate::storage::DoctorStorage;
+
+/// Mirror of `InstitutionData` in the institution registry contract.
+#[contracttype]
+#[derive(Clone)]
+pub struct InstitutionData {
+    pub name: String,
+    pub license_id: String,
+    pub metadata: String,
+    pub verified: bool,
+}
+
+/// The subset of the institution registry contract this contract calls.
+#[allow(dead_code)]
+#[contractclient(name = "InstitutionRegistryClient")]
+pub trait InstitutionRegistryInterface {
+    fn get_institution(env: Env, wallet: Address) -> InstitutionData;
+}
+
+/// Fails unless `institution` is registered and verified in the configured
+/// institution registry.
+pub fn require_verified_institution(env: &Env, institution: &Address) -> Result<(), DoctorError> {
+    let registry = DoctorStorage::new(env).get_institution_registry()?;
+
+    match InstitutionRegistryClient::new(env, &registry).try_get_institution(institution) {
+        Ok(Ok(data)) if data.verified => Ok(()),
+        _ => Err(DoctorError::InstitutionNotVerified),
+    }
+}
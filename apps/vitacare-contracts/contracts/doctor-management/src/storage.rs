@@ -1,6 +1,6 @@
-use soroban_sdk::{Env, Address, Symbol};
+use soroban_sdk::{Env, Address, String, Symbol, Vec};
 
-use crate::types::DoctorProfile;
+use crate::types::{DoctorProfile, ProfileVersion};
 use crate::errors::DoctorError;
 
 pub struct DoctorStorage {
@@ -31,6 +31,123 @@ impl DoctorStorage {
         self.env.storage().persistent().has(&key)
     }
 
+    pub fn get_version_count(&self, wallet: &Address) -> u32 {
+        self.env
+            .storage()
+            .persistent()
+            .get(&(Symbol::new(&self.env, "VERSIONS"), wallet.clone()))
+            .unwrap_or(0)
+    }
+
+    pub fn get_version(&self, wallet: &Address, version: u32) -> Result<ProfileVersion, DoctorError> {
+        self.env
+            .storage()
+            .persistent()
+            .get(&(Symbol::new(&self.env, "VERSION"), wallet.clone(), version))
+            .ok_or(DoctorError::DoctorNotFound)
+    }
+
+    /// Appends `entry` to the version log of `wallet`. Versions are never
+    /// overwritten.
+    pub fn save_version(&self, wallet: &Address, entry: &ProfileVersion) {
+        let storage = self.env.storage().persistent();
+        storage.set(&(Symbol::new(&self.env, "VERSION"), wallet.clone(), entry.version), entry);
+        storage.set(&(Symbol::new(&self.env, "VERSIONS"), wallet.clone()), &(entry.version + 1));
+    }
+
+    pub fn has_admin(&self) -> bool {
+        self.env.storage().instance().has(&Symbol::new(&self.env, "ADMIN"))
+    }
+
+    pub fn get_admin(&self) -> Result<Address, DoctorError> {
+        self.env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&self.env, "ADMIN"))
+            .ok_or(DoctorError::NotInitialized)
+    }
+
+    pub fn set_admin(&self, admin: &Address) {
+        self.env.storage().instance().set(&Symbol::new(&self.env, "ADMIN"), admin);
+    }
+
+    pub fn get_institution_registry(&self) -> Result<Address, DoctorError> {
+        self.env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&self.env, "REGISTRY"))
+            .ok_or(DoctorError::NotInitialized)
+    }
+
+    pub fn set_institution_registry(&self, registry: &Address) {
+        self.env.storage().instance().set(&Symbol::new(&self.env, "REGISTRY"), registry);
+    }
+
+    pub fn get_specialization_index(&self, specialization: &String) -> Vec<Address> {
+        self.get_index(&self.specialization_key(specialization))
+    }
+
+    pub fn add_to_specialization_index(&self, specialization: &String, wallet: &Address) {
+        self.add_to_index(&self.specialization_key(specialization), wallet);
+    }
+
+    pub fn remove_from_specialization_index(&self, specialization: &String, wallet: &Address) {
+        self.remove_from_index(&self.specialization_key(specialization), wallet);
+    }
+
+    pub fn get_institution_index(&self, institution: &Address) -> Vec<Address> {
+        self.get_index(&self.institution_key(institution))
+    }
+
+    pub fn add_to_institution_index(&self, institution: &Address, wallet: &Address) {
+        self.add_to_index(&self.institution_key(institution), wallet);
+    }
+
+    pub fn remove_from_institution_index(&self, institution: &Address, wallet: &Address) {
+        self.remove_from_index(&self.institution_key(institution), wallet);
+    }
+
+    fn get_index<K>(&self, key: &K) -> Vec<Address>
+    where
+        K: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+    {
+        self.env
+            .storage()
+            .persistent()
+            .get(key)
+            .unwrap_or_else(|| Vec::new(&self.env))
+    }
+
+    fn add_to_index<K>(&self, key: &K, wallet: &Address)
+    where
+        K: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+    {
+        let mut wallets = self.get_index(key);
+        if !wallets.contains(wallet) {
+            wallets.push_back(wallet.clone());
+            self.env.storage().persistent().set(key, &wallets);
+        }
+    }
+
+    fn remove_from_index<K>(&self, key: &K, wallet: &Address)
+    where
+        K: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+    {
+        let mut wallets = self.get_index(key);
+        if let Some(index) = wallets.first_index_of(wallet) {
+            wallets.remove(index);
+            self.env.storage().persistent().set(key, &wallets);
+        }
+    }
+
+    fn specialization_key(&self, specialization: &String) -> (Symbol, String) {
+        (Symbol::new(&self.env, "SPEC"), specialization.clone())
+    }
+
+    fn institution_key(&self, institution: &Address) -> (Symbol, Address) {
+        (Symbol::new(&self.env, "INST"), institution.clone())
+    }
+
     fn get_storage_key(&self, wallet: &Address) -> (Symbol, Address) {
         (Symbol::new(&self.env, "DOCTOR"), wallet.clone())
     }
@@ -1,22 +1,55 @@
 #![cfg(test)]
 
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
-    Address, Env, String, Vec,
+    testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke},
+    Address, Env, IntoVal, String, Vec,
 };
+use vitacare_institution_registry::{InstitutionRegistry, InstitutionRegistryClient};
 
 use crate::{
+    types::{AffiliationStatus, DoctorStatus, ProfileField},
     DoctorManagementContract, DoctorManagementContractClient,
 };
 
-fn create_test_contract(e: &Env) -> DoctorManagementContractClient {
+fn create_test_contract(e: &Env) -> DoctorManagementContractClient<'_> {
     // Set up initial ledger state
     e.ledger().with_mut(|l| {
         l.timestamp = 1234567890;
     });
-    
+    e.mock_all_auths();
+
+    let registry_id = e.register(InstitutionRegistry, ());
     let contract_id = e.register(DoctorManagementContract {}, ());
-    DoctorManagementContractClient::new(e, &contract_id)
+    let contract = DoctorManagementContractClient::new(e, &contract_id);
+    contract.initialize(&Address::generate(e), &registry_id);
+    contract
+}
+
+// Registers an institution in the contract's registry, verified when `verified` is set
+fn register_test_institution(
+    e: &Env,
+    contract: &DoctorManagementContractClient,
+    verified: bool,
+) -> Address {
+    let registry = InstitutionRegistryClient::new(e, &contract.get_institution_registry());
+    let registry_admin = Address::generate(e);
+    registry.set_admin(&registry_admin);
+
+    let institution = Address::generate(e);
+    registry.register_institution(
+        &institution,
+        &String::from_str(e, "General Hospital"),
+        &String::from_str(e, "GH12345"),
+        &String::from_str(e, "{}"),
+    );
+    if verified {
+        registry.verify_institution(&registry_admin, &institution);
+    }
+    institution
+}
+
+fn create_test_institution(e: &Env, contract: &DoctorManagementContractClient) -> Address {
+    register_test_institution(e, contract, true)
 }
 
 fn create_test_doctor(
@@ -32,6 +65,7 @@ fn create_test_doctor(
         &String::from_str(e, name),
         &String::from_str(e, specialization),
         institution,
+        &String::from_str(e, "Attending Physician"),
     );
 }
 
@@ -41,7 +75,7 @@ fn test_create_doctor_profile() {
     let contract = create_test_contract(&e);
     
     let wallet = Address::generate(&e);
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
     let name = "Dr. John Doe";
     let specialization = "Cardiology";
 
@@ -51,6 +85,7 @@ fn test_create_doctor_profile() {
         &String::from_str(&e, name),
         &String::from_str(&e, specialization),
         &institution,
+        &String::from_str(&e, "Attending Physician"),
     );
 
     // Verify the profile was created correctly
@@ -58,7 +93,13 @@ fn test_create_doctor_profile() {
     assert_eq!(profile.wallet, wallet);
     assert_eq!(profile.name, String::from_str(&e, name));
     assert_eq!(profile.specialization, String::from_str(&e, specialization));
-    assert_eq!(profile.institution_wallet, institution);
+    assert_eq!(profile.affiliations.len(), 1);
+    let affiliation = profile.affiliations.get(0).unwrap();
+    assert_eq!(affiliation.institution, institution);
+    assert_eq!(affiliation.role, String::from_str(&e, "Attending Physician"));
+    assert_eq!(affiliation.status, AffiliationStatus::Active);
+    assert_eq!(affiliation.start_date, 1234567890);
+    assert_eq!(affiliation.end_date, None);
     assert_eq!(profile.metadata, String::from_str(&e, ""));
     
     // Verify timestamps
@@ -75,7 +116,7 @@ fn test_create_duplicate_doctor_profile() {
     let contract = create_test_contract(&e);
     
     let wallet = Address::generate(&e);
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
 
     // Create first profile
     create_test_doctor(
@@ -104,7 +145,7 @@ fn test_update_doctor_profile() {
     let contract = create_test_contract(&e);
     
     let wallet = Address::generate(&e);
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
 
     // Create initial profile
     create_test_doctor(
@@ -176,7 +217,7 @@ fn test_multiple_doctors() {
     let e = Env::default();
     let contract = create_test_contract(&e);
     
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
     let doctor1 = Address::generate(&e);
     let doctor2 = Address::generate(&e);
 
@@ -216,7 +257,7 @@ fn test_update_timestamps() {
     let contract = create_test_contract(&e);
     
     let wallet = Address::generate(&e);
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
 
     // Create initial profile
     create_test_doctor(
@@ -258,7 +299,7 @@ fn test_empty_string_updates() {
     let contract = create_test_contract(&e);
     
     let wallet = Address::generate(&e);
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
 
     // Create initial profile
     create_test_doctor(
@@ -288,7 +329,7 @@ fn test_same_institution_multiple_doctors() {
     let e = Env::default();
     let contract = create_test_contract(&e);
     
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
     let mut doctors = Vec::new(&e);
     
     // Create multiple doctors for the same institution
@@ -312,7 +353,7 @@ fn test_same_institution_multiple_doctors() {
     // Verify all doctors are associated with the same institution
     for doctor in doctors.iter() {
         let profile = contract.get_doctor_profile(&doctor);
-        assert_eq!(profile.institution_wallet, institution);
+        assert!(profile.is_affiliated_with(&institution));
     }
 }
 
@@ -322,7 +363,7 @@ fn test_metadata_persistence() {
     let contract = create_test_contract(&e);
     
     let wallet = Address::generate(&e);
-    let institution = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
 
     // Create profile with initial metadata
     create_test_doctor(
@@ -351,4 +392,589 @@ fn test_metadata_persistence() {
         let profile = contract.get_doctor_profile(&wallet);
         assert_eq!(profile.metadata, String::from_str(&e, metadata));
     }
-} 
\ No newline at end of file
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_initialize_twice() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    contract.initialize(&Address::generate(&e), &Address::generate(&e));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_create_with_unverified_institution() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let institution = register_test_institution(&e, &contract, false);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &institution);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_create_with_unregistered_institution() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let institution = Address::generate(&e);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &institution);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_create_requires_institution_signature() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
+    let name = String::from_str(&e, "Dr. John Doe");
+    let specialization = String::from_str(&e, "Cardiology");
+    let role = String::from_str(&e, "Attending Physician");
+
+    // Only the doctor signs
+    contract
+        .mock_auths(&[MockAuth {
+            address: &wallet,
+            invoke: &MockAuthInvoke {
+                contract: &contract.address,
+                fn_name: "create_doctor_profile",
+                args: (
+                    wallet.clone(),
+                    name.clone(),
+                    specialization.clone(),
+                    institution.clone(),
+                    role.clone(),
+                )
+                    .into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .create_doctor_profile(&wallet, &name, &specialization, &institution, &role);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_update_requires_doctor_signature() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &institution);
+
+    let specialization = Some(String::from_str(&e, "Neurology"));
+    let metadata: Option<String> = None;
+
+    // Only the institution signs
+    contract
+        .mock_auths(&[MockAuth {
+            address: &institution,
+            invoke: &MockAuthInvoke {
+                contract: &contract.address,
+                fn_name: "update_doctor_profile",
+                args: (wallet.clone(), specialization.clone(), metadata.clone()).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .update_doctor_profile(&wallet, &specialization, &metadata);
+}
+
+#[test]
+fn test_list_doctors_by_specialization() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let institution = create_test_institution(&e, &contract);
+    let cardiologist = Address::generate(&e);
+    let second_cardiologist = Address::generate(&e);
+    let neurologist = Address::generate(&e);
+    create_test_doctor(&e, &contract, &cardiologist, "Dr. John", "Cardiology", &institution);
+    create_test_doctor(&e, &contract, &second_cardiologist, "Dr. Jane", " cardiology ", &institution);
+    create_test_doctor(&e, &contract, &neurologist, "Dr. Bob", "Neurology", &institution);
+
+    let page = contract.list_doctors_by_specialization(&String::from_str(&e, "CARDIOLOGY"), &0, &10);
+    assert_eq!(page.doctors.len(), 2);
+    assert_eq!(page.doctors.get(0).unwrap().wallet, cardiologist);
+    assert_eq!(page.doctors.get(1).unwrap().wallet, second_cardiologist);
+    assert_eq!(page.next_cursor, None);
+
+    let page = contract.list_doctors_by_specialization(&String::from_str(&e, "Dermatology"), &0, &10);
+    assert_eq!(page.doctors.len(), 0);
+}
+
+#[test]
+fn test_specialization_index_follows_updates() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let institution = create_test_institution(&e, &contract);
+    let wallet = Address::generate(&e);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John", "Cardiology", &institution);
+
+    contract.update_doctor_profile(&wallet, &Some(String::from_str(&e, "Pediatric Cardiology")), &None);
+
+    let cardiology = contract.list_doctors_by_specialization(&String::from_str(&e, "Cardiology"), &0, &10);
+    assert_eq!(cardiology.doctors.len(), 0);
+
+    let pediatric =
+        contract.list_doctors_by_specialization(&String::from_str(&e, "pediatric cardiology"), &0, &10);
+    assert_eq!(pediatric.doctors.len(), 1);
+    assert_eq!(
+        pediatric.doctors.get(0).unwrap().specialization,
+        String::from_str(&e, "Pediatric Cardiology")
+    );
+}
+
+#[test]
+fn test_list_doctors_by_institution_paginates() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let institution = create_test_institution(&e, &contract);
+    let other_institution = create_test_institution(&e, &contract);
+    let mut doctors = Vec::new(&e);
+    for name in ["Dr. John", "Dr. Jane", "Dr. Bob"].iter() {
+        let doctor = Address::generate(&e);
+        create_test_doctor(&e, &contract, &doctor, name, "Cardiology", &institution);
+        doctors.push_back(doctor);
+    }
+    create_test_doctor(&e, &contract, &Address::generate(&e), "Dr. Alice", "Cardiology", &other_institution);
+
+    let first = contract.list_doctors_by_institution(&institution, &0, &2);
+    assert_eq!(first.doctors.len(), 2);
+    assert_eq!(first.doctors.get(0).unwrap().wallet, doctors.get(0).unwrap());
+    assert_eq!(first.doctors.get(1).unwrap().wallet, doctors.get(1).unwrap());
+    assert_eq!(first.next_cursor, Some(2));
+
+    let second = contract.list_doctors_by_institution(&institution, &2, &2);
+    assert_eq!(second.doctors.len(), 1);
+    assert_eq!(second.doctors.get(0).unwrap().wallet, doctors.get(2).unwrap());
+    assert_eq!(second.next_cursor, None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_list_doctors_invalid_page_size() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let institution = create_test_institution(&e, &contract);
+    contract.list_doctors_by_institution(&institution, &0, &0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_create_with_overlong_specialization() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
+    create_test_doctor(
+        &e,
+        &contract,
+        &wallet,
+        "Dr. John Doe",
+        "Cardiology, Interventional Cardiology & Cardiac Electrophysiology",
+        &institution,
+    );
+}
+
+#[test]
+fn test_create_with_longest_specialization() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let institution = create_test_institution(&e, &contract);
+    let specialization = "Cardiology, Interventional Cardiology, Cardiac Electrophysiology";
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", specialization, &institution);
+
+    let profile = contract.get_doctor_profile(&wallet);
+    assert_eq!(profile.specialization, String::from_str(&e, specialization));
+}
+
+#[test]
+fn test_affiliation_requires_institution_confirmation() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    let clinic = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.request_affiliation(&wallet, &clinic, &String::from_str(&e, "Consultant"), &1234500000);
+
+    let profile = contract.get_doctor_profile(&wallet);
+    let requested = profile.affiliations.get(1).unwrap();
+    assert_eq!(requested.status, AffiliationStatus::Pending);
+    assert_eq!(requested.start_date, 1234500000);
+    assert!(!profile.is_affiliated_with(&clinic));
+    assert_eq!(contract.list_doctors_by_institution(&clinic, &0, &10).doctors.len(), 0);
+
+    contract.confirm_affiliation(&clinic, &wallet);
+
+    let profile = contract.get_doctor_profile(&wallet);
+    assert!(profile.is_affiliated_with(&hospital));
+    assert!(profile.is_affiliated_with(&clinic));
+    assert_eq!(contract.list_doctors_by_institution(&clinic, &0, &10).doctors.len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_affiliation_without_start_date_fails() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    let clinic = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.request_affiliation(&wallet, &clinic, &String::from_str(&e, "Consultant"), &0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_affiliation_starting_far_ahead_fails() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    let clinic = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    // Two years after the ledger time set up by create_test_contract
+    let start_date = 1234567890 + 2 * 365 * 24 * 60 * 60;
+    contract.request_affiliation(&wallet, &clinic, &String::from_str(&e, "Consultant"), &start_date);
+}
+
+#[test]
+fn test_end_affiliation_keeps_history() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    e.ledger().with_mut(|l| l.timestamp = 1234600000);
+    contract.end_affiliation(&hospital, &wallet, &hospital);
+
+    let profile = contract.get_doctor_profile(&wallet);
+    assert_eq!(profile.affiliations.len(), 1);
+    let ended = profile.affiliations.get(0).unwrap();
+    assert_eq!(ended.status, AffiliationStatus::Ended);
+    assert_eq!(ended.end_date, Some(1234600000));
+    assert!(!profile.is_affiliated_with(&hospital));
+    assert_eq!(contract.list_doctors_by_institution(&hospital, &0, &10).doctors.len(), 0);
+
+    // The doctor can rejoin later; the old affiliation stays in the history
+    contract.request_affiliation(&wallet, &hospital, &String::from_str(&e, "Consultant"), &1234600000);
+    contract.confirm_affiliation(&hospital, &wallet);
+
+    let profile = contract.get_doctor_profile(&wallet);
+    assert_eq!(profile.affiliations.len(), 2);
+    assert_eq!(profile.affiliations.get(0).unwrap().status, AffiliationStatus::Ended);
+    assert_eq!(profile.affiliations.get(1).unwrap().status, AffiliationStatus::Active);
+}
+
+#[test]
+fn test_doctor_can_end_affiliation() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.end_affiliation(&wallet, &wallet, &hospital);
+
+    let profile = contract.get_doctor_profile(&wallet);
+    assert_eq!(profile.affiliations.get(0).unwrap().status, AffiliationStatus::Ended);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_third_party_cannot_end_affiliation() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.end_affiliation(&Address::generate(&e), &wallet, &hospital);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_duplicate_affiliation_request() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.request_affiliation(&wallet, &hospital, &String::from_str(&e, "Consultant"), &1234567890);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_confirm_without_request() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    let clinic = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.confirm_affiliation(&clinic, &wallet);
+}
+
+#[test]
+fn test_profile_history_records_changes() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    e.ledger().with_mut(|l| l.timestamp = 1234600000);
+    contract.update_doctor_profile(
+        &wallet,
+        &Some(String::from_str(&e, "Pediatric Cardiology")),
+        &Some(String::from_str(&e, "Board Certified")),
+    );
+
+    e.ledger().with_mut(|l| l.timestamp = 1234700000);
+    contract.end_affiliation(&hospital, &wallet, &hospital);
+
+    let history = contract.get_profile_history(&wallet, &0, &10);
+    assert_eq!(history.versions.len(), 3);
+    assert_eq!(history.next_cursor, None);
+
+    let created = history.versions.get(0).unwrap();
+    assert_eq!(created.version, 0);
+    assert_eq!(created.changed_at, 1234567890);
+    assert_eq!(created.signer, wallet);
+    assert_eq!(created.changed_fields.get(0).unwrap(), ProfileField::Created);
+    assert_eq!(created.profile.specialization, String::from_str(&e, "Cardiology"));
+
+    let updated = history.versions.get(1).unwrap();
+    assert_eq!(updated.changed_at, 1234600000);
+    assert_eq!(updated.signer, wallet);
+    assert_eq!(updated.changed_fields.len(), 2);
+    assert_eq!(updated.changed_fields.get(0).unwrap(), ProfileField::Specialization);
+    assert_eq!(updated.changed_fields.get(1).unwrap(), ProfileField::Metadata);
+
+    let ended = history.versions.get(2).unwrap();
+    assert_eq!(ended.signer, hospital);
+    assert_eq!(ended.changed_fields.get(0).unwrap(), ProfileField::Affiliations);
+
+    let page = contract.get_profile_history(&wallet, &1, &1);
+    assert_eq!(page.versions.len(), 1);
+    assert_eq!(page.versions.get(0).unwrap().version, 1);
+    assert_eq!(page.next_cursor, Some(2));
+}
+
+#[test]
+fn test_update_without_changes_adds_no_version() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    e.ledger().with_mut(|l| l.timestamp = 1234600000);
+    contract.update_doctor_profile(&wallet, &None, &None);
+    contract.update_doctor_profile(&wallet, &Some(String::from_str(&e, "Cardiology")), &None);
+
+    let history = contract.get_profile_history(&wallet, &0, &10);
+    assert_eq!(history.versions.len(), 1);
+    assert_eq!(contract.get_doctor_profile(&wallet).updated_at, 1234567890);
+}
+
+#[test]
+fn test_get_doctor_profile_at() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    e.ledger().with_mut(|l| l.timestamp = 1234600000);
+    contract.update_doctor_profile(&wallet, &Some(String::from_str(&e, "Neurology")), &None);
+
+    e.ledger().with_mut(|l| l.timestamp = 1234700000);
+    contract.update_doctor_profile(&wallet, &Some(String::from_str(&e, "Oncology")), &None);
+
+    let at_creation = contract.get_doctor_profile_at(&wallet, &1234567890);
+    assert_eq!(at_creation.specialization, String::from_str(&e, "Cardiology"));
+
+    let before_second = contract.get_doctor_profile_at(&wallet, &1234699999);
+    assert_eq!(before_second.specialization, String::from_str(&e, "Neurology"));
+
+    let latest = contract.get_doctor_profile_at(&wallet, &1234800000);
+    assert_eq!(latest.specialization, String::from_str(&e, "Oncology"));
+    assert_eq!(latest, contract.get_doctor_profile(&wallet));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_get_doctor_profile_before_creation() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.get_doctor_profile_at(&wallet, &1234567889);
+}
+
+#[test]
+fn test_doctor_sets_leave_and_retirement() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+    assert_eq!(contract.get_doctor_status(&wallet), DoctorStatus::Active);
+
+    e.ledger().with_mut(|l| l.timestamp = 1234600000);
+    contract.set_doctor_status(&wallet, &wallet, &DoctorStatus::OnLeave, &String::from_str(&e, "Parental leave"));
+
+    let profile = contract.get_doctor_profile(&wallet);
+    assert_eq!(profile.status, DoctorStatus::OnLeave);
+    assert_eq!(profile.status_reason, String::from_str(&e, "Parental leave"));
+    assert_eq!(profile.status_changed_at, 1234600000);
+
+    contract.set_doctor_status(&wallet, &wallet, &DoctorStatus::Active, &String::from_str(&e, ""));
+    contract.set_doctor_status(&wallet, &wallet, &DoctorStatus::Retired, &String::from_str(&e, "Retirement"));
+    assert_eq!(contract.get_doctor_status(&wallet), DoctorStatus::Retired);
+
+    let history = contract.get_profile_history(&wallet, &0, &10);
+    assert_eq!(history.versions.len(), 4);
+    assert_eq!(history.versions.get(3).unwrap().changed_fields.get(0).unwrap(), ProfileField::Status);
+}
+
+#[test]
+fn test_institution_suspends_and_reinstates() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.set_doctor_status(&hospital, &wallet, &DoctorStatus::Suspended, &String::from_str(&e, "Under review"));
+
+    let profile = contract.get_doctor_profile(&wallet);
+    assert_eq!(profile.status, DoctorStatus::Suspended);
+    assert_eq!(profile.status_reason, String::from_str(&e, "Under review"));
+
+    contract.set_doctor_status(&hospital, &wallet, &DoctorStatus::Active, &String::from_str(&e, "Cleared"));
+    assert_eq!(contract.get_doctor_status(&wallet), DoctorStatus::Active);
+}
+
+#[test]
+fn test_admin_suspends_doctor() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let registry_id = e.register(InstitutionRegistry, ());
+    let contract_id = e.register(DoctorManagementContract {}, ());
+    let contract = DoctorManagementContractClient::new(&e, &contract_id);
+    contract.initialize(&admin, &registry_id);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.set_doctor_status(&admin, &wallet, &DoctorStatus::Suspended, &String::from_str(&e, "License revoked"));
+    assert_eq!(contract.get_doctor_status(&wallet), DoctorStatus::Suspended);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_doctor_cannot_suspend_self() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.set_doctor_status(&wallet, &wallet, &DoctorStatus::Suspended, &String::from_str(&e, ""));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_suspended_doctor_cannot_reinstate_self() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+    contract.set_doctor_status(&hospital, &wallet, &DoctorStatus::Suspended, &String::from_str(&e, "Under review"));
+
+    contract.set_doctor_status(&wallet, &wallet, &DoctorStatus::Active, &String::from_str(&e, ""));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_unaffiliated_institution_cannot_suspend() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    let clinic = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.set_doctor_status(&clinic, &wallet, &DoctorStatus::Suspended, &String::from_str(&e, ""));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_suspension_lifts_only_to_active() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+    contract.set_doctor_status(&hospital, &wallet, &DoctorStatus::Suspended, &String::from_str(&e, "Under review"));
+
+    contract.set_doctor_status(&hospital, &wallet, &DoctorStatus::Retired, &String::from_str(&e, ""));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_set_same_status() {
+    let e = Env::default();
+    let contract = create_test_contract(&e);
+
+    let wallet = Address::generate(&e);
+    let hospital = create_test_institution(&e, &contract);
+    create_test_doctor(&e, &contract, &wallet, "Dr. John Doe", "Cardiology", &hospital);
+
+    contract.set_doctor_status(&wallet, &wallet, &DoctorStatus::Active, &String::from_str(&e, ""));
+}
@@ -1,4 +1,43 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
+
+/// Longest specialization, in bytes, accepted for the directory index
+pub const MAX_SPECIALIZATION_LEN: usize = 64;
+
+/// Most profiles returned by one directory query
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Furthest ahead of the current ledger time, in seconds, an affiliation may start
+pub const MAX_AFFILIATION_LEAD_TIME: u64 = 365 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AffiliationStatus {
+    /// Requested by the doctor, awaiting the institution's confirmation
+    Pending,
+    /// Confirmed by the institution
+    Active,
+    /// Ended or declined by either side; kept for the history
+    Ended,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Affiliation {
+    pub institution: Address,
+    pub role: String,
+    pub start_date: u64,
+    pub end_date: Option<u64>,
+    pub status: AffiliationStatus,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DoctorStatus {
+    Active,
+    OnLeave,
+    Suspended,
+    Retired,
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -6,8 +45,11 @@ pub struct DoctorProfile {
     pub wallet: Address,
     pub name: String,
     pub specialization: String,
-    pub institution_wallet: Address,
+    pub affiliations: Vec<Affiliation>,
     pub metadata: String,
+    pub status: DoctorStatus,
+    pub status_reason: String,
+    pub status_changed_at: u64,
     pub created_at: u64,
     pub updated_at: u64,
 }
@@ -18,16 +60,72 @@ impl DoctorProfile {
         wallet: Address,
         name: String,
         specialization: String,
-        institution_wallet: Address,
+        affiliations: Vec<Affiliation>,
     ) -> Self {
         Self {
             wallet,
             name,
             specialization,
-            institution_wallet,
+            affiliations,
             metadata: String::from_str(env, ""),
+            status: DoctorStatus::Active,
+            status_reason: String::from_str(env, ""),
+            status_changed_at: env.ledger().timestamp(),
             created_at: env.ledger().timestamp(),
             updated_at: env.ledger().timestamp(),
         }
     }
-} 
\ No newline at end of file
+
+    /// Position of the pending or active affiliation with `institution`, if any
+    pub fn open_affiliation(&self, institution: &Address) -> Option<u32> {
+        self.affiliations.iter().position(|affiliation| {
+            affiliation.institution == *institution && affiliation.status != AffiliationStatus::Ended
+        }).map(|index| index as u32)
+    }
+
+    /// Whether `institution` has confirmed a current affiliation with this doctor
+    pub fn is_affiliated_with(&self, institution: &Address) -> bool {
+        self.affiliations.iter().any(|affiliation| {
+            affiliation.institution == *institution && affiliation.status == AffiliationStatus::Active
+        })
+    }
+}
+
+/// One page of a directory listing. `next_cursor` is `None` on the last page.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DoctorPage {
+    pub doctors: Vec<DoctorProfile>,
+    pub next_cursor: Option<u32>,
+}
+
+/// Part of a profile touched by a change
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProfileField {
+    Created,
+    Specialization,
+    Metadata,
+    Affiliations,
+    Status,
+}
+
+/// One entry of a doctor's append-only version log: the profile as it stood
+/// after the change, what changed, when, and who signed it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProfileVersion {
+    pub version: u32,
+    pub changed_fields: Vec<ProfileField>,
+    pub changed_at: u64,
+    pub signer: Address,
+    pub profile: DoctorProfile,
+}
+
+/// One page of a profile's version log, oldest first
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProfileHistoryPage {
+    pub versions: Vec<ProfileVersion>,
+    pub next_cursor: Option<u32>,
+}
@@ -1,12 +1,6 @@
 use soroban_sdk::{Address, Env, Map, symbol_short};
 use crate::types::InstitutionData;
 
-/// Storage key for the institutions map
-const INSTITUTIONS_KEY: &[u8] = b"inst";
-
-/// Storage key for the admin address
-const ADMIN_KEY: &[u8] = b"admin";
-
 /// Retrieves the institutions map from storage
 ///
 /// # Arguments
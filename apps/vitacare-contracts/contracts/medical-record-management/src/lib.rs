@@ -8,21 +8,21 @@ mod access;
 mod test; 
 
 use soroban_sdk::{contract, contractimpl, Env, String, Vec, Address, BytesN, log};
-use types::RecordMetadata;
+use types::{RecordId, RecordMetadata};
 
 #[contract]
 pub struct MedicalRecordsContract;
 
 #[contractimpl]
 impl MedicalRecordsContract {
-    /// Add a new medical record for a patient
+    /// Add a new medical record for a patient and return its ID
     pub fn add_medical_record(
         env: Env,
         patient: Address,
         doctor: Address,
         record_hash: BytesN<32>,
         description: String,
-    ) -> Result<(), error::Error> {
+    ) -> Result<RecordId, error::Error> {
         log!(&env, "add_medical_record: Verificando autorización para doctor: {:?}", doctor);
         
         // Verificar si el doctor está autorizado
@@ -1,5 +1,5 @@
 use soroban_sdk::{Env, Vec, Address, BytesN, String, log};
-use crate::types::RecordMetadata;
+use crate::types::{RecordId, RecordMetadata};
 use crate::error::Error;
 use crate::storage;
 
@@ -10,7 +10,7 @@ pub fn add_record(
     doctor: &Address,
     record_hash: &BytesN<32>,
     description: &String,
-) -> Result<(), Error> {
+) -> Result<RecordId, Error> {
     // Get new record ID from counter
     let record_id = storage::increment_record_id_counter(env, patient);
     
@@ -26,7 +26,7 @@ pub fn add_record(
     // Save record to storage
     storage::save_record(env, patient, &record);
     
-    Ok(())
+    Ok(record_id)
 }
 
 /// Get all medical records for a patient
@@ -31,12 +31,13 @@ fn test_medical_record_flow() {
 
     // Test 3: Doctor adds a medical record
     env.mock_all_auths();
-    client.add_medical_record(&patient, &doctor, &record_hash, &description);
+    let record_id = client.add_medical_record(&patient, &doctor, &record_hash, &description);
 
     // Test 4: Patient can view their records
     let records = client.get_medical_records(&patient);
     assert_eq!(records.len(), 1);
     let record = records.get(0).unwrap();
+    assert_eq!(record.id, record_id);
     assert_eq!(record.doctor, doctor);
     assert_eq!(record.patient, patient);
     assert_eq!(record.data_id, record_hash);